@@ -0,0 +1,64 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Type};
+
+/// How the generated conversion code reports errors.
+///
+/// The generated body always propagates errors as a `Result`. Panicking conversions unwrap that
+/// result at the end, while fallible conversions return it to the caller.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConversionMode {
+    Panicking,
+    Fallible,
+}
+
+impl ConversionMode {
+    pub fn from_java_trait(self) -> TokenStream {
+        match self {
+            ConversionMode::Panicking => quote! { jnix::FromJava },
+            ConversionMode::Fallible => quote! { jnix::TryFromJava },
+        }
+    }
+
+    pub fn into_java_trait(self) -> TokenStream {
+        match self {
+            ConversionMode::Panicking => quote! { jnix::IntoJava },
+            ConversionMode::Fallible => quote! { jnix::TryIntoJava },
+        }
+    }
+
+    /// Generates an expression that converts a Java value into the Rust type, resulting in a
+    /// `Result`.
+    pub fn from_java_call(self, target_type: &Type, source: TokenStream) -> TokenStream {
+        match self {
+            ConversionMode::Panicking => quote! {
+                Ok::<_, jnix::JnixError>(
+                    <#target_type as jnix::FromJava<_>>::from_java(env, #source)
+                )
+            },
+            ConversionMode::Fallible => quote! {
+                <#target_type as jnix::TryFromJava<_>>::try_from_java(env, #source)
+            },
+        }
+    }
+
+    /// Generates an expression that converts a Rust value into its Java type, resulting in a
+    /// `Result`.
+    pub fn into_java_call(self, source: &Ident) -> TokenStream {
+        match self {
+            ConversionMode::Panicking => quote! {
+                Ok::<_, jnix::JnixError>(jnix::IntoJava::into_java(#source, env))
+            },
+            ConversionMode::Fallible => quote! {
+                jnix::TryIntoJava::try_into_java(#source, env)
+            },
+        }
+    }
+
+    /// Generates an expression to obtain the JNI signature of a Rust value's Java type.
+    pub fn jni_signature_of(self, source: &Ident) -> TokenStream {
+        let into_java_trait = self.into_java_trait();
+
+        quote! { #into_java_trait::jni_signature(&#source) }
+    }
+}
@@ -0,0 +1,456 @@
+use heck::ToLowerCamelCase;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    AttributeArgs, FnArg, GenericArgument, Ident, ItemFn, Lit, LitStr, Meta, NestedMeta,
+    PathArguments, ReturnType, Type,
+};
+
+pub struct ExportedFunction {
+    function: ItemFn,
+    jni_class_name: String,
+    method_name: String,
+    signature: Option<String>,
+    symbol_name: Ident,
+    takes_env: bool,
+    parameter_types: Vec<Type>,
+    return_kind: ReturnKind,
+}
+
+enum ReturnKind {
+    Unit,
+    Value(Type),
+    Result(Option<Type>),
+}
+
+impl ExportedFunction {
+    pub fn new(attribute_arguments: AttributeArgs, function: ItemFn) -> Result<Self, syn::Error> {
+        let mut class_name = None;
+        let mut method_name = None;
+        let mut signature = None;
+
+        for argument in attribute_arguments {
+            match argument {
+                NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                    let key = name_value.path.get_ident().ok_or_else(|| {
+                        syn::Error::new_spanned(
+                            &name_value.path,
+                            "Invalid jnix export attribute key",
+                        )
+                    })?;
+
+                    let literal = match &name_value.lit {
+                        Lit::Str(literal) => literal,
+                        literal => {
+                            return Err(syn::Error::new_spanned(
+                                literal,
+                                "Invalid jnix export attribute value, expected a string",
+                            ))
+                        }
+                    };
+
+                    match key.to_string().as_str() {
+                        "class" => class_name = Some(literal.value()),
+                        "name" => method_name = Some(literal.value()),
+                        "signature" => {
+                            if argument_signature(&literal.value()).is_none() {
+                                return Err(syn::Error::new_spanned(
+                                    literal,
+                                    "Invalid JNI method signature in jnix export attribute",
+                                ));
+                            }
+
+                            signature = Some(literal.value());
+                        }
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                key,
+                                format!("Unknown jnix export attribute key: {}", key),
+                            ))
+                        }
+                    }
+                }
+                argument => {
+                    return Err(syn::Error::new_spanned(
+                        argument,
+                        "Invalid jnix export attribute, expected `class = \"...\"`, \
+                         `name = \"...\"` or `signature = \"...\"`",
+                    ))
+                }
+            }
+        }
+
+        let class_name = class_name.ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "Missing Java class name in jnix export attribute, expected `class = \"...\"`",
+            )
+        })?;
+        let method_name =
+            method_name.unwrap_or_else(|| function.sig.ident.to_string().to_lower_camel_case());
+        let symbol_name = Ident::new(
+            &mangle_symbol_name(&class_name, &method_name, signature.as_deref()),
+            function.sig.ident.span(),
+        );
+
+        let mut parameter_types = function
+            .sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(parameter) => Ok((*parameter.ty).clone()),
+                FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+                    receiver,
+                    "Can't export a method that receives `self`",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let takes_env = parameter_types.first().map(is_jnix_env_reference) == Some(true);
+
+        if takes_env {
+            parameter_types.remove(0);
+        }
+
+        let return_kind = match &function.sig.output {
+            ReturnType::Default => ReturnKind::Unit,
+            ReturnType::Type(_, return_type) => ReturnKind::from(return_type.as_ref()),
+        };
+
+        Ok(ExportedFunction {
+            function,
+            jni_class_name: class_name.replace(".", "/"),
+            method_name,
+            signature,
+            symbol_name,
+            takes_env,
+            parameter_types,
+            return_kind,
+        })
+    }
+
+    pub fn generate(self) -> TokenStream {
+        let function = &self.function;
+        let function_name = &self.function.sig.ident;
+        let symbol_name = &self.symbol_name;
+
+        let bindings: Vec<_> = (0..self.parameter_types.len())
+            .map(|index| Ident::new(&format!("jnix_argument_{}", index), Span::call_site()))
+            .collect();
+        let parameter_types = &self.parameter_types;
+        let jni_parameter_types: Vec<_> = parameter_types
+            .iter()
+            .map(|parameter_type| jni_type_for(parameter_type, quote! { 'env }))
+            .collect();
+
+        let env_argument = if self.takes_env {
+            Some(quote! { env, })
+        } else {
+            None
+        };
+
+        let call = quote! { #function_name(#env_argument #( #bindings ),*) };
+
+        let (return_type, conversion) = match &self.return_kind {
+            ReturnKind::Unit => (quote! {}, quote! { #call; }),
+            ReturnKind::Result(None) => (quote! {}, quote! { env.unwrap_or_throw(#call); }),
+            ReturnKind::Value(value_type) => (
+                jni_return_type_for(value_type),
+                quote! { jnix::IntoJava::into_java(#call, env) },
+            ),
+            ReturnKind::Result(Some(value_type)) => (
+                jni_return_type_for(value_type),
+                quote! { env.into_java_or_throw(#call) },
+            ),
+        };
+
+        let registration = self.generate_registration();
+
+        quote! {
+            #function
+
+            #registration
+
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub extern "system" fn #symbol_name<'env>(
+                env: jnix::jni::JNIEnv<'env>,
+                _: jnix::jni::objects::JObject<'env>,
+                #( #bindings: #jni_parameter_types ),*
+            ) #return_type {
+                let env = jnix::JnixEnv::from(env);
+
+                env.catch_panic(|env| {
+                    #(
+                        let #bindings =
+                            <#parameter_types as jnix::FromJava<'env, #jni_parameter_types>>
+                                ::from_java(env, #bindings);
+                    )*
+
+                    #conversion
+                })
+            }
+        }
+    }
+}
+
+impl ExportedFunction {
+    fn generate_registration(&self) -> TokenStream {
+        let jni_class_name = LitStr::new(&self.jni_class_name, Span::call_site());
+        let method_name = LitStr::new(&self.method_name, Span::call_site());
+        let symbol_name = &self.symbol_name;
+
+        let parameter_signatures: Vec<_> = self
+            .parameter_types
+            .iter()
+            .map(|parameter_type| {
+                let jni_type = jni_type_for(parameter_type, quote! { 'static });
+
+                quote! { <#parameter_type as jnix::FromJava<'static, #jni_type>>::JNI_SIGNATURE }
+            })
+            .collect();
+
+        let return_signature = match &self.return_kind {
+            ReturnKind::Unit | ReturnKind::Result(None) => quote! { "V" },
+            ReturnKind::Value(value_type) | ReturnKind::Result(Some(value_type)) => {
+                quote! { <#value_type as jnix::IntoJava<'static, 'static>>::JNI_SIGNATURE }
+            }
+        };
+
+        let signature_check = self.signature.as_ref().map(|signature| {
+            quote! {
+                const _: () = {
+                    const PARTS: &[&str] = &["(", #( #parameter_signatures, )* ")", #return_signature];
+                    const LENGTH: usize = jnix::const_concat::concatenated_length(PARTS);
+                    const BYTES: jnix::const_concat::Concatenated<LENGTH> =
+                        jnix::const_concat::concatenate(PARTS);
+
+                    assert!(
+                        jnix::const_concat::equals(jnix::const_concat::as_str(&BYTES), #signature),
+                        concat!(
+                            "The JNI signature ",
+                            #signature,
+                            " in the jnix export attribute doesn't match the parameter and return \
+                             types",
+                        ),
+                    );
+                };
+            }
+        });
+
+        quote! {
+            #signature_check
+
+            jnix::inventory::submit! {
+                unsafe {
+                    jnix::NativeMethod::new(
+                        #jni_class_name,
+                        #method_name,
+                        &[ #( #parameter_signatures ),* ],
+                        #return_signature,
+                        #symbol_name as *mut std::ffi::c_void,
+                    )
+                }
+            }
+        }
+    }
+}
+
+impl From<&Type> for ReturnKind {
+    fn from(return_type: &Type) -> Self {
+        if let Type::Tuple(tuple) = return_type {
+            if tuple.elems.is_empty() {
+                return ReturnKind::Unit;
+            }
+        }
+
+        if let Some(ok_type) = result_ok_type(return_type) {
+            return match ok_type {
+                Type::Tuple(tuple) if tuple.elems.is_empty() => ReturnKind::Result(None),
+                ok_type => ReturnKind::Result(Some(ok_type)),
+            };
+        }
+
+        ReturnKind::Value(return_type.clone())
+    }
+}
+
+/// Returns the success type if the type is a `Result`.
+fn result_ok_type(result_type: &Type) -> Option<Type> {
+    let path = match result_type {
+        Type::Path(path) => &path.path,
+        _ => return None,
+    };
+    let last_segment = path.segments.last()?;
+
+    if last_segment.ident != "Result" {
+        return None;
+    }
+
+    match &last_segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            GenericArgument::Type(ok_type) => Some(ok_type.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_jnix_env_reference(parameter_type: &Type) -> bool {
+    match parameter_type {
+        Type::Reference(reference) => match reference.elem.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident == "JnixEnv")
+                .unwrap_or(false),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns the raw JNI type used to pass a value of the Rust type.
+///
+/// The type is selected by the `JniArgument` trait, so that it's also resolved for type aliases,
+/// qualified paths and wrapper types.
+fn jni_type_for(rust_type: &Type, lifetime: TokenStream) -> TokenStream {
+    quote! { <#rust_type as jnix::JniArgument<#lifetime>>::JniType }
+}
+
+fn jni_return_type_for(rust_type: &Type) -> TokenStream {
+    quote! {
+        -> <<#rust_type as jnix::IntoJava<'env, 'env>>::JavaType as jnix::JniReturnValue>::JniType
+    }
+}
+
+/// Returns the part of a JNI method signature with the parameter types.
+fn argument_signature(signature: &str) -> Option<&str> {
+    let (arguments, _) = signature.strip_prefix('(')?.split_once(')')?;
+
+    Some(arguments)
+}
+
+/// Builds the symbol name that the JVM looks up for a native method.
+///
+/// The long symbol name, which includes the parameter types, is used if the method signature is
+/// specified, so that overloaded methods have distinct symbol names. See the [JNI specification]
+/// for the name mangling rules.
+///
+/// [JNI specification]: https://docs.oracle.com/javase/8/docs/technotes/guides/jni/spec/design.html#resolving_native_method_names
+fn mangle_symbol_name(class_name: &str, method_name: &str, signature: Option<&str>) -> String {
+    let mut symbol_name = String::from("Java_");
+
+    mangle_qualified_name_into(&mut symbol_name, class_name);
+    symbol_name.push('_');
+    mangle_into(&mut symbol_name, method_name);
+
+    if let Some(arguments) = signature.and_then(argument_signature) {
+        symbol_name.push_str("__");
+        mangle_qualified_name_into(&mut symbol_name, arguments);
+    }
+
+    symbol_name
+}
+
+/// Escapes a class name or a type signature, replacing the package separators with underscores.
+fn mangle_qualified_name_into(output: &mut String, name: &str) {
+    for (index, part) in name
+        .split(|character| character == '.' || character == '/')
+        .enumerate()
+    {
+        if index > 0 {
+            output.push('_');
+        }
+
+        mangle_into(output, part);
+    }
+}
+
+fn mangle_into(output: &mut String, name: &str) {
+    for character in name.chars() {
+        match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => output.push(character),
+            '_' => output.push_str("_1"),
+            ';' => output.push_str("_2"),
+            '[' => output.push_str("_3"),
+            _ => {
+                let mut utf16_units = [0; 2];
+
+                for unit in character.encode_utf16(&mut utf16_units) {
+                    output.push_str(&format!("_0{:04x}", unit));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mangle_symbol_name;
+
+    #[test]
+    fn plain_names_are_not_escaped() {
+        assert_eq!(
+            mangle_symbol_name("my.package.MyClass", "getData", None),
+            "Java_my_package_MyClass_getData"
+        );
+        assert_eq!(
+            mangle_symbol_name("my/package/MyClass", "getData", None),
+            "Java_my_package_MyClass_getData"
+        );
+    }
+
+    #[test]
+    fn underscores_are_escaped() {
+        assert_eq!(
+            mangle_symbol_name("my_package.My_Class", "get_data", None),
+            "Java_my_1package_My_1Class_get_1data"
+        );
+    }
+
+    #[test]
+    fn inner_class_separators_are_escaped() {
+        assert_eq!(
+            mangle_symbol_name("my.package.Outer$Inner", "run", None),
+            "Java_my_package_Outer_00024Inner_run"
+        );
+    }
+
+    #[test]
+    fn non_ascii_characters_are_escaped_as_utf16() {
+        assert_eq!(
+            mangle_symbol_name("my.package.Größe", "füü", None),
+            "Java_my_package_Gr_000f6_000dfe_f_000fc_000fc"
+        );
+        assert_eq!(
+            mangle_symbol_name("my.package.Emoji", "smile😀", None),
+            "Java_my_package_Emoji_smile_0d83d_0de00"
+        );
+    }
+
+    #[test]
+    fn overloaded_names_include_the_parameter_types() {
+        assert_eq!(
+            mangle_symbol_name("my.package.MyClass", "add", Some("(II)I")),
+            "Java_my_package_MyClass_add__II"
+        );
+        assert_eq!(
+            mangle_symbol_name(
+                "my.package.MyClass",
+                "add",
+                Some("(Ljava/lang/String;[JLmy/package/My_Class;)V")
+            ),
+            "Java_my_package_MyClass_add__Ljava_lang_String_2_3JLmy_package_My_1Class_2"
+        );
+    }
+
+    #[test]
+    fn methods_without_parameters_have_an_empty_overloaded_suffix() {
+        assert_eq!(
+            mangle_symbol_name("my.package.MyClass", "reset", Some("()V")),
+            "Java_my_package_MyClass_reset__"
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use crate::{JnixAttributes, TypeParameters};
+use crate::{ConversionMode, JnixAttributes, TypeParameters};
 use heck::ToLowerCamelCase;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{
-    parse_str, spanned::Spanned, ExprClosure, Field, Fields, Ident, Index, LitStr, Member, Pat,
-    PatType, Token, Type,
+    parse_quote, parse_str, spanned::Spanned, ExprClosure, Field, Fields, Ident, Index, LitStr,
+    Member, Pat, PatType, Token, Type,
 };
 
 pub struct ParsedField {
@@ -72,14 +72,91 @@ impl ParsedField {
         LitStr::new(&getter_name, self.span)
     }
 
+    pub fn rust_name(&self) -> LitStr {
+        let rust_name = match &self.member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        };
+
+        LitStr::new(&rust_name, self.span)
+    }
+
     pub fn binding(&self, prefix: &str) -> Ident {
         Ident::new(&format!("_{}_{}", prefix, self.name), self.span)
     }
 
+    /// Returns the wrapper that selects how the field is converted, if it has a
+    /// `#[jnix(unsigned = "...")]`, `#[jnix(char = "...")]`, `#[jnix(lone_surrogates = "...")]` or
+    /// `#[jnix(collection = "...")]` attribute that selects a conversion other than the default.
+    ///
+    /// The wrapper is returned as the path of its constructor and the wrapped type.
+    pub fn conversion_wrapper(&self) -> Option<(TokenStream, Type)> {
+        let field_type = self.get_type();
+
+        if let Some(policy) = self.attributes.get_value("unsigned") {
+            let wrapper = match policy.value().as_str() {
+                "reinterpret" => return None,
+                "widen" => quote! { jnix::Widened },
+                "checked" => quote! { jnix::Checked },
+                _ => panic!(
+                    "Invalid policy in jnix(unsigned = ...) attribute, expected \"reinterpret\", \
+                     \"widen\" or \"checked\""
+                ),
+            };
+
+            Some((wrapper.clone(), parse_quote! { #wrapper<#field_type> }))
+        } else if let Some(representation) = self.attributes.get_value("char") {
+            let wrapper = match representation.value().as_str() {
+                "char" => return None,
+                "string" => quote! { jnix::CharString },
+                "code_point" => quote! { jnix::CodePoint },
+                _ => panic!(
+                    "Invalid representation in jnix(char = ...) attribute, expected \"char\", \
+                     \"string\" or \"code_point\""
+                ),
+            };
+
+            Some((wrapper.clone(), parse_quote! { #wrapper }))
+        } else if let Some(policy) = self.attributes.get_value("lone_surrogates") {
+            let wrapper = match policy.value().as_str() {
+                "replace" => return None,
+                "fail" => quote! { jnix::StrictString },
+                _ => panic!(
+                    "Invalid policy in jnix(lone_surrogates = ...) attribute, expected \
+                     \"replace\" or \"fail\""
+                ),
+            };
+
+            Some((wrapper.clone(), parse_quote! { #wrapper }))
+        } else if let Some(representation) = self.attributes.get_value("collection") {
+            let wrapper = match representation.value().as_str() {
+                "array" => quote! { jnix::JavaArray },
+                "list" => quote! { jnix::JavaList },
+                _ => panic!(
+                    "Invalid representation in jnix(collection = ...) attribute, expected \
+                     \"array\" or \"list\""
+                ),
+            };
+
+            Some((wrapper.clone(), parse_quote! { #wrapper<#field_type> }))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the type that is converted to and from Java, which is the conversion wrapper type if
+    /// there is one.
+    pub fn conversion_type(&self) -> Type {
+        match self.conversion_wrapper() {
+            Some((_, wrapper_type)) => wrapper_type,
+            None => self.get_type().clone(),
+        }
+    }
+
     pub fn preconversion(&self) -> TokenStream {
         let jnix_source_object = &self.source_binding;
 
-        match self.attributes.get_value("map") {
+        let value = match self.attributes.get_value("map") {
             Some(closure_string_literal) => {
                 let mut closure = parse_str(&closure_string_literal.value())
                     .expect("Invalid closure syntax in jnix(map = ...) attribute");
@@ -89,6 +166,11 @@ impl ParsedField {
                 quote! { (#closure)(#jnix_source_object) }
             }
             None => quote! { #jnix_source_object },
+        };
+
+        match self.conversion_wrapper() {
+            Some((wrapper, _)) => quote! { #wrapper(#value) },
+            None => value,
         }
     }
 
@@ -189,29 +271,33 @@ impl ParsedFields {
     pub fn generate_struct_from_java(
         &self,
         jni_class_name_literal: &LitStr,
-        class_name: &str,
+        type_name_literal: &LitStr,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         self.generate_from_java(
             jni_class_name_literal,
-            class_name,
+            type_name_literal,
             type_parameters,
             quote! { Self },
+            mode,
         )
     }
 
     pub fn generate_enum_variant_from_java(
         &self,
         jni_class_name_literal: &LitStr,
-        class_name: &str,
+        variant_type_name_literal: &LitStr,
         variant: &Ident,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         self.generate_from_java(
             jni_class_name_literal,
-            class_name,
+            variant_type_name_literal,
             type_parameters,
             quote! { Self::#variant },
+            mode,
         )
     }
 
@@ -219,16 +305,16 @@ impl ParsedFields {
         &self,
         jni_class_name_literal: &LitStr,
         type_name_literal: &LitStr,
-        class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         let source_bindings = self.source_bindings();
         let members = self.members();
         let conversion = self.generate_into_java_conversion(
             jni_class_name_literal,
             type_name_literal,
-            class_name,
             type_parameters,
+            mode,
         );
 
         quote! {
@@ -240,17 +326,17 @@ impl ParsedFields {
     pub fn generate_enum_variant_into_java(
         &self,
         jni_class_name_literal: &LitStr,
-        type_name_literal: &LitStr,
-        class_name: &str,
+        variant_type_name_literal: &LitStr,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         let source_bindings = self.source_bindings();
         let original_bindings = self.original_bindings();
         let conversion = self.generate_into_java_conversion(
             jni_class_name_literal,
-            type_name_literal,
-            class_name,
+            variant_type_name_literal,
             type_parameters,
+            mode,
         );
 
         quote! {
@@ -262,31 +348,32 @@ impl ParsedFields {
     fn generate_from_java(
         &self,
         jni_class_name_literal: &LitStr,
-        class_name: &str,
+        type_name_literal: &LitStr,
         type_parameters: &TypeParameters,
         constructor_name: TokenStream,
+        mode: ConversionMode,
     ) -> TokenStream {
         let names = self.original_bindings();
         let constructor = self.generate_enum_variant_parameters();
-        let conversions = self.generate_from_java_conversions(class_name, type_parameters);
-        let class_binding = if self.fields.is_empty() {
-            quote! {}
-        } else {
-            quote! { let class = env.get_class(#jni_class_name_literal); }
-        };
-
+        let conversions = self.generate_from_java_conversions(
+            jni_class_name_literal,
+            type_name_literal,
+            type_parameters,
+            mode,
+        );
         quote! {
-            #class_binding
             #( let #names = { #conversions }; )*
 
-            #constructor_name #constructor
+            Ok(#constructor_name #constructor)
         }
     }
 
-    fn generate_from_java_conversions<'a, 'b: 'a, 'c: 'a>(
+    fn generate_from_java_conversions<'a, 'b: 'a, 'c: 'a, 'd: 'a>(
         &'a self,
-        class_name: &'b str,
-        type_parameters: &'c TypeParameters,
+        jni_class_name_literal: &'b LitStr,
+        type_name_literal: &'c LitStr,
+        type_parameters: &'d TypeParameters,
+        mode: ConversionMode,
     ) -> impl Iterator<Item = TokenStream> + 'a {
         self.fields.iter().map(move |field| {
             let field_type = field.get_type();
@@ -295,31 +382,89 @@ impl ParsedFields {
                 quote! { <#field_type as std::default::Default>::default() }
             } else {
                 let getter = field.getter();
+                let rust_name = field.rust_name();
+                let conversion_type = field.conversion_type();
                 let jni_signature;
+                let method_signature;
 
                 if let Some(signature) = type_parameters.erased_type_for(&field_type) {
-                    jni_signature = quote! { #signature }
+                    jni_signature = quote! { #signature };
+                    method_signature = Some(quote! { concat!("()", #signature) });
                 } else {
+                    let from_java_trait = mode.from_java_trait();
+
                     jni_signature = quote! {
-                        <#field_type as jnix::FromJava<jnix::jni::objects::JValue>>::JNI_SIGNATURE
+                        <#conversion_type as #from_java_trait<jnix::jni::objects::JValue>>
+                            ::JNI_SIGNATURE
+                    };
+
+                    if type_parameters.may_be_used_in_type(&field_type) {
+                        method_signature = None;
+                    } else {
+                        method_signature = Some(const_concatenation(&[
+                            quote! { "()" },
+                            jni_signature.clone(),
+                        ]));
                     }
                 }
 
+                let (method_signature, method_signature_value) = match method_signature {
+                    Some(constant) => (
+                        quote! {
+                            const METHOD_SIGNATURE: &str = #constant;
+                            let method_signature = METHOD_SIGNATURE;
+                        },
+                        quote! { method_signature },
+                    ),
+                    None => (
+                        quote! { let method_signature = format!("(){}", jni_signature); },
+                        quote! { method_signature.clone() },
+                    ),
+                };
+
+                let conversion = mode.from_java_call(&conversion_type, quote! { java_value });
+                let conversion = match field.conversion_wrapper() {
+                    Some(_) => quote! { #conversion.map(|wrapped| wrapped.0) },
+                    None => conversion,
+                };
+
                 quote! {
                     let jni_signature = #jni_signature;
-                    let method_signature = format!("(){}", jni_signature);
-                    let method_id = env.get_method_id(&class, #getter, &method_signature)
-                        .expect(concat!(
-                            "Failed to get method ID for ", #class_name, "::", #getter
-                        ));
-                    let return_type = jni_signature.parse().unwrap_or_else(|_| {
-                        panic!("Invalid JNI signature: {}", jni_signature);
-                    });
-
-                    let java_value = env.call_method_unchecked(jnix_source_object, method_id, return_type, &[])
-                        .expect(concat!("Failed to call ", #class_name, "::", #getter));
-
-                    <#field_type as jnix::FromJava<_>>::from_java(env, java_value)
+                    #method_signature
+                    let jni_error = |error: jnix::jni::errors::Error| {
+                        env.convert_jni_error(error)
+                            .with_class(#jni_class_name_literal)
+                            .with_member(#getter, #method_signature_value)
+                    };
+
+                    let conversion = || -> Result<#field_type, jnix::JnixError> {
+                        let method_id = env.get_cached_method_id(
+                            #jni_class_name_literal,
+                            #getter,
+                            &method_signature,
+                        )?;
+                        let return_type = jnix::call_type::java_type_for_call(jni_signature)
+                            .map_err(jni_error)?;
+
+                        let java_value = env
+                            .call_method_unchecked(jnix_source_object, method_id, return_type, &[])
+                            .map_err(jni_error)?;
+
+                        let field_value = #conversion;
+
+                        if let jnix::jni::objects::JValue::Object(object) = java_value {
+                            let _ = env.delete_local_ref(object);
+                        }
+
+                        field_value
+                    };
+
+                    conversion().map_err(|error| {
+                        error.with_context(format!(
+                            concat!(#type_name_literal, ".", #rust_name, " via ", #getter, "{}"),
+                            method_signature,
+                        ))
+                    })?
                 }
             }
         })
@@ -329,44 +474,119 @@ impl ParsedFields {
         &self,
         jni_class_name_literal: &LitStr,
         type_name_literal: &LitStr,
-        class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
-        let signature_bindings = self.bindings("signature").collect();
+        let signatures: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|field| !field.skip)
+            .map(|field| Self::into_java_signature(field, type_parameters, mode))
+            .collect();
+        let signature_bindings: Vec<_> = self.bindings("signature").collect();
         let final_bindings = self.bindings("final").collect();
-        let declarations = self.declarations(&signature_bindings, &final_bindings, type_parameters);
+
+        let is_constant = signatures.iter().all(|(_, is_constant)| *is_constant);
+
+        let (signature_declarations, constructor_signature) = if is_constant {
+            let parts = std::iter::once(quote! { "(" })
+                .chain(signatures.iter().map(|(signature, _)| signature.clone()))
+                .chain(std::iter::once(quote! { ")V" }))
+                .collect::<Vec<_>>();
+            let constant = const_concatenation(&parts);
+
+            let constructor_signature = quote! {
+                const CONSTRUCTOR_SIGNATURE: &str = #constant;
+                let constructor_signature = CONSTRUCTOR_SIGNATURE;
+            };
+
+            (vec![None; signatures.len()], constructor_signature)
+        } else {
+            let signature_declarations = signature_bindings
+                .iter()
+                .zip(&signatures)
+                .map(|(binding, (signature, _))| Some(quote! { let #binding = #signature; }))
+                .collect();
+
+            let constructor_signature = quote! {
+                let mut constructor_signature = String::with_capacity(
+                    1 + #( #signature_bindings.as_bytes().len() + )* 2
+                );
+
+                constructor_signature.push_str("(");
+                #( constructor_signature.push_str(#signature_bindings); )*
+                constructor_signature.push_str(")V");
+            };
+
+            (signature_declarations, constructor_signature)
+        };
+
+        let declarations = self.declarations(
+            &signature_declarations,
+            &final_bindings,
+            type_name_literal,
+            mode,
+        );
 
         quote! {
             #( #declarations )*
 
-            let mut constructor_signature = String::with_capacity(
-                1 + #( #signature_bindings.as_bytes().len() + )* 2
-            );
-
-            constructor_signature.push_str("(");
-            #( constructor_signature.push_str(#signature_bindings); )*
-            constructor_signature.push_str(")V");
+            #constructor_signature
 
             let parameters = [ #( jnix::AsJValue::as_jvalue(&#final_bindings) ),* ];
 
-            let class = env.get_class(#jni_class_name_literal);
-            let object = env.new_object(&class, constructor_signature, &parameters)
-                .expect(concat!("Failed to convert ",
-                    #type_name_literal,
-                    " Rust type into ",
-                    #class_name,
-                    " Java object",
-                ));
+            let class = env.try_get_class(#jni_class_name_literal)?;
+            let constructor_id = env
+                .get_cached_method_id(#jni_class_name_literal, "<init>", &constructor_signature)
+                .map_err(|error| error.with_context(#type_name_literal))?;
+            let object = env.new_object_unchecked(&class, constructor_id, &parameters)
+                .map_err(|error| {
+                    env.convert_jni_error(error)
+                        .with_class(#jni_class_name_literal)
+                        .with_member("<init>", constructor_signature.to_owned())
+                        .with_context(#type_name_literal)
+                })?;
+
+            Ok(env.auto_local(object))
+        }
+    }
 
-            env.auto_local(object)
+    /// Returns the JNI signature of the Java type a field is converted into, and whether the
+    /// signature can be used to build a constant.
+    fn into_java_signature(
+        field: &ParsedField,
+        type_parameters: &TypeParameters,
+        mode: ConversionMode,
+    ) -> (TokenStream, bool) {
+        let field_type = field.get_type();
+
+        if let Some(target) = field.attributes.get_value("target_class") {
+            let signature = format!("L{};", target.value().replace(".", "/"));
+
+            (quote! { #signature }, true)
+        } else if let Some(signature) = type_parameters.erased_type_for(field_type) {
+            (quote! { #signature }, true)
+        } else if field.attributes.get_value("map").is_some()
+            || type_parameters.may_be_used_in_type(field_type)
+        {
+            (mode.jni_signature_of(&field.binding("converted")), false)
+        } else {
+            let into_java_trait = mode.into_java_trait();
+            let conversion_type = field.conversion_type();
+
+            (
+                quote! { <#conversion_type as #into_java_trait>::JNI_SIGNATURE },
+                true,
+            )
         }
     }
 
     fn declarations<'a, 'b, 'c, 'd, 'z>(
         &'a self,
-        signature_bindings: &'b Vec<Ident>,
+        signature_declarations: &'b Vec<Option<TokenStream>>,
         final_bindings: &'c Vec<Ident>,
-        type_parameters: &'d TypeParameters,
+        type_name_literal: &'d LitStr,
+        mode: ConversionMode,
     ) -> impl Iterator<Item = TokenStream> + 'z
     where
         'a: 'z,
@@ -377,25 +597,19 @@ impl ParsedFields {
         self.fields
             .iter()
             .filter(|field| !field.skip)
-            .zip(signature_bindings.iter().zip(final_bindings.iter()))
-            .map(move |(field, (signature_binding, final_binding))| {
+            .zip(signature_declarations.iter().zip(final_bindings.iter()))
+            .map(move |(field, (signature_declaration, final_binding))| {
                 let converted_binding = field.binding("converted");
                 let conversion = field.preconversion();
-
-                let signature = if let Some(target) = field.attributes.get_value("target_class") {
-                    let signature = format!("L{};", target.value().replace(".", "/"));
-
-                    quote! { #signature }
-                } else if let Some(signature) = type_parameters.erased_type_for(&field.get_type()) {
-                    quote! { #signature }
-                } else {
-                    quote! { #converted_binding.jni_signature() }
-                };
+                let rust_name = field.rust_name();
+                let into_java_call = mode.into_java_call(&converted_binding);
 
                 quote! {
                     let #converted_binding = #conversion;
-                    let #signature_binding = #signature;
-                    let #final_binding = #converted_binding.into_java(env);
+                    #signature_declaration
+                    let #final_binding = #into_java_call.map_err(|error| {
+                        error.with_context(concat!(#type_name_literal, ".", #rust_name))
+                    })?;
                 }
             })
     }
@@ -433,3 +647,15 @@ impl ParsedFields {
             .map(|field| &field.member)
     }
 }
+
+/// Generates a constant expression that concatenates string constants.
+fn const_concatenation(parts: &[TokenStream]) -> TokenStream {
+    quote! {{
+        const PARTS: &[&str] = &[ #( #parts ),* ];
+        const LENGTH: usize = jnix::const_concat::concatenated_length(PARTS);
+        const BYTES: jnix::const_concat::Concatenated<LENGTH> =
+            jnix::const_concat::concatenate(PARTS);
+
+        jnix::const_concat::as_str(&BYTES)
+    }}
+}
@@ -1,11 +1,12 @@
 use crate::JnixAttributes;
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::quote;
 use std::collections::HashMap;
 use syn::{Generics, Ident, Lifetime, Path, ReturnType, Token, Type, TypeParam, TypeParamBound};
 
 pub struct ParsedGenerics {
     type_bounds: HashMap<String, String>,
+    lifetime_names: Vec<String>,
     parameters: Vec<TokenStream>,
     lifetime_constraints: Vec<TokenStream>,
     type_constraints: Vec<TypeParam>,
@@ -15,6 +16,10 @@ impl ParsedGenerics {
     pub fn new(generics: &Generics, attributes: &JnixAttributes) -> Self {
         let (lifetimes, types) = Self::collect_generic_definitions(generics);
         let parameters = Self::collect_generic_params(&lifetimes, &types);
+        let lifetime_names = lifetimes
+            .iter()
+            .map(|lifetime| lifetime.ident.to_string())
+            .collect();
         let (lifetime_constraints, type_constraints) = Self::collect_constraints(generics);
         let bounds_attribute = attributes
             .get_value("bounds")
@@ -23,6 +28,7 @@ impl ParsedGenerics {
 
         ParsedGenerics {
             type_bounds,
+            lifetime_names,
             parameters,
             lifetime_constraints,
             type_constraints,
@@ -93,6 +99,7 @@ impl ParsedGenerics {
     pub fn type_parameters(&self) -> TypeParameters {
         TypeParameters {
             bounds: self.type_bounds.clone(),
+            lifetime_names: self.lifetime_names.clone(),
         }
     }
 
@@ -158,6 +165,7 @@ impl ParsedGenerics {
 
 pub struct TypeParameters {
     bounds: HashMap<String, String>,
+    lifetime_names: Vec<String>,
 }
 
 impl TypeParameters {
@@ -182,6 +190,29 @@ impl TypeParameters {
         }
     }
 
+    /// Checks if a type may depend on the generic parameters of the derived type.
+    ///
+    /// Items nested inside the generated code, like constants, can't refer to such types. The
+    /// check is conservative, so it may also return `true` for some types that don't depend on
+    /// the generic parameters.
+    pub fn may_be_used_in_type(&self, type_to_check: &Type) -> bool {
+        self.may_be_used_in_tokens(quote! { #type_to_check })
+    }
+
+    fn may_be_used_in_tokens(&self, tokens: TokenStream) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => {
+                let name = ident.to_string();
+
+                name == "Self"
+                    || self.bounds.contains_key(&name)
+                    || self.lifetime_names.contains(&name)
+            }
+            TokenTree::Group(group) => self.may_be_used_in_tokens(group.stream()),
+            TokenTree::Punct(_) | TokenTree::Literal(_) => false,
+        })
+    }
+
     fn is_used_in_type(&self, type_to_check: &Type) -> bool {
         match type_to_check {
             Type::Never(_) => false,
@@ -9,6 +9,8 @@
 extern crate proc_macro;
 
 mod attributes;
+mod conversion_mode;
+mod export;
 mod fields;
 mod generics;
 mod parsed_type;
@@ -16,13 +18,15 @@ mod variants;
 
 use crate::{
     attributes::JnixAttributes,
+    conversion_mode::ConversionMode,
+    export::ExportedFunction,
     fields::ParsedFields,
     generics::{ParsedGenerics, TypeParameters},
     parsed_type::ParsedType,
     variants::ParsedVariants,
 };
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, AttributeArgs, DeriveInput, ItemFn};
 
 /// Derives `FromJava` for a type.
 ///
@@ -51,6 +55,27 @@ use syn::{parse_macro_input, DeriveInput};
 /// In either case, fields can be skipped and constructed using `Default::default()` by using the
 /// `#[jnix(default)]` attribute.
 ///
+/// Unsigned integer fields are read from a Java type of the same size by default, reinterpreting
+/// the bits. The `#[jnix(unsigned = "widen")]` attribute reads them from the next larger Java type
+/// instead, and `#[jnix(unsigned = "checked")]` fails if the Java value is negative. See
+/// `jnix::Widened` and `jnix::Checked` for the Java types used.
+///
+/// `char` fields are read from a Java `char` by default. The `#[jnix(char = "string")]` attribute
+/// reads them from a `String` with a single character instead, and `#[jnix(char = "code_point")]`
+/// reads them from an `int` with the Unicode code point.
+///
+/// Lone surrogates in Java strings are replaced with the U+FFFD replacement character when
+/// `String` fields are read. The `#[jnix(lone_surrogates = "fail")]` attribute makes the
+/// conversion fail instead.
+///
+/// Sequence fields of primitive types, like `Vec<i32>` or `[u8; 16]`, are read from a Java
+/// primitive array by default. The `#[jnix(collection = "list")]` attribute reads a `Vec` from a
+/// `java.util.ArrayList` of boxed values instead. See `jnix::JavaList`.
+///
+/// `Vec` fields of other types are read from a `java.util.ArrayList` by default. The
+/// `#[jnix(collection = "array")]` attribute reads them from a Java object array instead. See
+/// `jnix::JavaArray`.
+///
 /// # Enums
 ///
 /// The generate `FromJava` implementation for an enum that only has unit variants (i.e, no tuple
@@ -205,7 +230,7 @@ use syn::{parse_macro_input, DeriveInput};
 pub fn derive_from_java(input: TokenStream) -> TokenStream {
     let parsed_type = ParsedType::new(parse_macro_input!(input as DeriveInput));
 
-    TokenStream::from(parsed_type.generate_from_java())
+    TokenStream::from(parsed_type.generate_from_java(ConversionMode::Panicking))
 }
 
 /// Derives `IntoJava` for a type.
@@ -234,6 +259,23 @@ pub fn derive_from_java(input: TokenStream) -> TokenStream {
 /// `#[jnix(target_class = "...")]` attribute. However, be aware that the target class must have
 /// the expected constructor with the parameter list based on the field order of the Rust type.
 ///
+/// Unsigned integer fields are converted to a Java type of the same size by default, reinterpreting
+/// the bits. The `#[jnix(unsigned = "widen")]` attribute converts them to the next larger Java type
+/// instead, and `#[jnix(unsigned = "checked")]` fails if the value doesn't fit in the signed Java
+/// type. See `jnix::Widened` and `jnix::Checked` for the Java types used.
+///
+/// `char` fields are converted to a Java `char` by default, which fails for characters that need a
+/// surrogate pair. The `#[jnix(char = "string")]` attribute converts them to a `String` instead,
+/// and `#[jnix(char = "code_point")]` converts them to an `int` with the Unicode code point.
+///
+/// Sequence fields of primitive types, like `Vec<i32>` or `[u8; 16]`, are converted to a Java
+/// primitive array by default. The `#[jnix(collection = "list")]` attribute converts a `Vec` to a
+/// `java.util.ArrayList` of boxed values instead. See `jnix::JavaList`.
+///
+/// `Vec` fields of other types are converted to a `java.util.ArrayList` by default. The
+/// `#[jnix(collection = "array")]` attribute converts them to a Java object array of the element
+/// class instead, like `String[]` for a `Vec<String>`. See `jnix::JavaArray`.
+///
 /// # Enums
 ///
 /// The generated `IntoJava` implementation for a enum that only has unit variants (i.e., no tuple
@@ -255,5 +297,112 @@ pub fn derive_from_java(input: TokenStream) -> TokenStream {
 pub fn derive_into_java(input: TokenStream) -> TokenStream {
     let parsed_type = ParsedType::new(parse_macro_input!(input as DeriveInput));
 
-    TokenStream::from(parsed_type.generate_into_java())
+    TokenStream::from(parsed_type.generate_into_java(ConversionMode::Panicking))
+}
+
+/// Derives `TryFromJava` for a type.
+///
+/// More specifically, `TryFromJava<'env, JObject<'sub_env>>` is derived for the type. This also
+/// makes available a `TryFromJava<'env, AutoLocal<'sub_env, 'borrow>>` implementation through a
+/// blanket implementation.
+///
+/// The generated code follows the same rules as the [`FromJava`][derive@FromJava] derivation, but
+/// instead of panicking it returns a `JnixError` if a getter method can't be found or called, or
+/// if a field value can't be converted. Therefore, the types of the fields must also implement
+/// `TryFromJava`. The errors are annotated with the Rust type, field and getter method involved in
+/// the failure, for example `MyData.number via getNumber()I`.
+#[proc_macro_derive(TryFromJava, attributes(jnix))]
+pub fn derive_try_from_java(input: TokenStream) -> TokenStream {
+    let parsed_type = ParsedType::new(parse_macro_input!(input as DeriveInput));
+
+    TokenStream::from(parsed_type.generate_from_java(ConversionMode::Fallible))
+}
+
+/// Derives `TryIntoJava` for a type.
+///
+/// The generated code follows the same rules as the [`IntoJava`][derive@IntoJava] derivation, but
+/// instead of panicking it returns a `JnixError` if the Java object can't be created, or if a
+/// field value can't be converted. Therefore, the types of the fields must also implement
+/// `TryIntoJava`. The errors are annotated with the Rust type and field involved in the failure.
+#[proc_macro_derive(TryIntoJava, attributes(jnix))]
+pub fn derive_try_into_java(input: TokenStream) -> TokenStream {
+    let parsed_type = ParsedType::new(parse_macro_input!(input as DeriveInput));
+
+    TokenStream::from(parsed_type.generate_into_java(ConversionMode::Fallible))
+}
+
+/// Derives `IntoJavaException` for a type.
+///
+/// The Java exception class must be specified using an attribute, like so:
+/// `#[jnix(exception_class = "my.package.MyException")]`. For enums, the attribute can also be
+/// used on a variant to throw a different exception class for that variant.
+///
+/// The message of the exception is obtained from the type's `Display` implementation, so the type
+/// must implement it. The exception class must have a constructor that receives the message as a
+/// `String`.
+#[proc_macro_derive(IntoJavaException, attributes(jnix))]
+pub fn derive_into_java_exception(input: TokenStream) -> TokenStream {
+    let parsed_type = ParsedType::new(parse_macro_input!(input as DeriveInput));
+
+    TokenStream::from(parsed_type.generate_into_java_exception())
+}
+
+/// Exports a Rust function as the implementation of a Java native method.
+///
+/// The Java class that declares the native method must be specified using an attribute argument,
+/// like so: `#[jnix::export(class = "my.package.MyClass")]`. The name of the Java method is the
+/// Rust function name converted to mixed case, unless it is specified with a `name = "..."`
+/// argument.
+///
+/// An `extern "system"` function is generated with the symbol name that the JVM expects for the
+/// native method, with the special characters in the class and method names escaped according to
+/// the JNI specification. The generated function:
+///
+/// 1. wraps the `JNIEnv` in a `JnixEnv`;
+/// 2. converts each argument using `FromJava`;
+/// 3. calls the Rust function;
+/// 4. converts the returned value using `IntoJava`.
+///
+/// Each argument is received as the raw JNI type selected by `jnix::JniArgument`, so arguments
+/// converted from Java primitives (like `i32`, `bool` or `Widened<u8>`) are received as the
+/// respective JNI primitive type, and all other arguments are received as object references. The
+/// `this` object (or the class, for static methods) is ignored. The first parameter of the Rust
+/// function can be a `&JnixEnv` reference, and in that case it receives the JNI environment.
+///
+/// Overloaded native methods need distinct symbol names, which include the parameter types. They
+/// are used if the JNI signature of the method is specified with a `signature = "..."` argument,
+/// like `#[jnix::export(class = "my.package.MyClass", name = "add", signature = "(II)I")]`. The
+/// signature is checked at compile time against the signature computed from the argument and
+/// return types.
+///
+/// If the Rust function returns a `Result`, an error is thrown as a Java exception using
+/// `IntoJavaException`. Panics are also caught and thrown as Java exceptions, using
+/// `JnixEnv::catch_panic`.
+///
+/// The message of the exception thrown for a panic only includes the location of the panic if the
+/// panic hook of `JnixEnv::install_panic_hook` is installed. `JnixEnv::register_natives` installs
+/// it, but if the exported functions are bound by the JVM through their symbol names instead,
+/// `JnixEnv::install_panic_hook` must be called explicitly, usually from `JNI_OnLoad`.
+///
+/// The exported function is also added to a registry of native methods, with a JNI signature
+/// computed from the `FromJava::JNI_SIGNATURE` of the argument types and the
+/// `IntoJava::JNI_SIGNATURE` of the return type. Calling `JnixEnv::register_natives` (usually from
+/// `JNI_OnLoad`) binds all registered methods using `RegisterNatives`, so that the JVM doesn't have
+/// to resolve the symbol names.
+///
+/// ```rust,ignore
+/// #[jnix::export(class = "my.package.JniClass")]
+/// fn get_data(env: &JnixEnv, number: i32) -> Result<MyData, MyError> {
+///     // Exported as `Java_my_package_JniClass_getData`
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn export(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let attribute_arguments = parse_macro_input!(attribute as AttributeArgs);
+    let function = parse_macro_input!(item as ItemFn);
+
+    match ExportedFunction::new(attribute_arguments, function) {
+        Ok(exported_function) => TokenStream::from(exported_function.generate()),
+        Err(error) => TokenStream::from(error.to_compile_error()),
+    }
 }
@@ -1,4 +1,6 @@
-use crate::{JnixAttributes, ParsedFields, ParsedGenerics, ParsedVariants, TypeParameters};
+use crate::{
+    ConversionMode, JnixAttributes, ParsedFields, ParsedGenerics, ParsedVariants, TypeParameters,
+};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{Data, DeriveInput, Ident, LitStr};
@@ -24,15 +26,17 @@ impl ParsedType {
         }
     }
 
-    pub fn generate_from_java(self) -> TokenStream {
+    pub fn generate_from_java(self, mode: ConversionMode) -> TokenStream {
         let class_name = self.class_name();
 
         let type_name = self.type_name;
+        let type_name_literal = LitStr::new(&type_name.to_string(), Span::call_site());
 
+        let from_java_trait = mode.from_java_trait();
         let trait_parameters = vec![quote! { 'env }, quote! { 'sub_env }];
         let trait_constraint = vec![quote! { 'env: 'sub_env }];
         let extra_type_bound =
-            vec![quote! { jnix::FromJava<'env, jnix::jni::objects::JValue<'sub_env>> }];
+            vec![quote! { #from_java_trait<'env, jnix::jni::objects::JValue<'sub_env>> }];
 
         let impl_generics = self.generics.impl_generics(trait_parameters.clone());
         let trait_generics = quote! { <'env, jnix::jni::objects::JObject<'sub_env>> };
@@ -44,37 +48,62 @@ impl ParsedType {
         let jni_class_name = class_name.replace(".", "/");
         let jni_class_name_literal = LitStr::new(&jni_class_name, Span::call_site());
 
+        let registration = self
+            .data
+            .generate_class_registration(&type_name_literal, &jni_class_name);
+
         let body = self.data.generate_from_java_body(
             &jni_class_name_literal,
+            &type_name_literal,
             &class_name,
             &self.generics.type_parameters(),
+            mode,
         );
 
-        quote! {
-            impl #impl_generics jnix::FromJava #trait_generics for #type_name #type_generics
-            #where_clause
-            {
-                const JNI_SIGNATURE: &'static str = concat!("L", #jni_class_name_literal, ";");
-
+        let conversion_method = match mode {
+            ConversionMode::Panicking => quote! {
                 fn from_java(
                     env: &jnix::JnixEnv<'env>,
                     jnix_source_object: jnix::jni::objects::JObject<'sub_env>,
                 ) -> Self {
+                    let result = (|| -> Result<Self, jnix::JnixError> { #body })();
+
+                    result.unwrap_or_else(|error| panic!("{}", error))
+                }
+            },
+            ConversionMode::Fallible => quote! {
+                fn try_from_java(
+                    env: &jnix::JnixEnv<'env>,
+                    jnix_source_object: jnix::jni::objects::JObject<'sub_env>,
+                ) -> Result<Self, jnix::JnixError> {
                     #body
                 }
+            },
+        };
+
+        quote! {
+            #registration
+
+            impl #impl_generics #from_java_trait #trait_generics for #type_name #type_generics
+            #where_clause
+            {
+                const JNI_SIGNATURE: &'static str = concat!("L", #jni_class_name_literal, ";");
+
+                #conversion_method
             }
         }
     }
 
-    pub fn generate_into_java(self) -> TokenStream {
+    pub fn generate_into_java(self, mode: ConversionMode) -> TokenStream {
         let class_name = self.class_name();
 
         let type_name = self.type_name;
         let type_name_literal = LitStr::new(&type_name.to_string(), Span::call_site());
 
+        let into_java_trait = mode.into_java_trait();
         let trait_parameters = vec![quote! { 'borrow }, quote! { 'env }];
         let trait_constraint = vec![quote! { 'env: 'borrow }];
-        let extra_type_bound = vec![quote! { jnix::IntoJava<'borrow, 'env> }];
+        let extra_type_bound = vec![quote! { #into_java_trait<'borrow, 'env> }];
 
         let impl_generics = self.generics.impl_generics(trait_parameters.clone());
         let trait_generics = quote! { < #( #trait_parameters ),* > };
@@ -86,24 +115,88 @@ impl ParsedType {
         let jni_class_name = class_name.replace(".", "/");
         let jni_class_name_literal = LitStr::new(&jni_class_name, Span::call_site());
 
+        let registration = self
+            .data
+            .generate_class_registration(&type_name_literal, &jni_class_name);
+
         let body = self.data.generate_into_java_body(
             &jni_class_name_literal,
             &type_name_literal,
             &class_name,
             &self.generics.type_parameters(),
+            mode,
         );
 
+        let conversion_method = match mode {
+            ConversionMode::Panicking => quote! {
+                #[allow(non_snake_case)]
+                fn into_java(self, env: &'borrow jnix::JnixEnv<'env>) -> Self::JavaType {
+                    let result = (move || -> Result<Self::JavaType, jnix::JnixError> {
+                        #body
+                    })();
+
+                    result.unwrap_or_else(|error| panic!("{}", error))
+                }
+            },
+            ConversionMode::Fallible => quote! {
+                #[allow(non_snake_case)]
+                fn try_into_java(
+                    self,
+                    env: &'borrow jnix::JnixEnv<'env>,
+                ) -> Result<Self::JavaType, jnix::JnixError> {
+                    #body
+                }
+            },
+        };
+
         quote! {
-            impl #impl_generics jnix::IntoJava #trait_generics for #type_name #type_generics
+            #registration
+
+            impl #impl_generics #into_java_trait #trait_generics for #type_name #type_generics
             #where_clause
             {
                 const JNI_SIGNATURE: &'static str = concat!("L", #jni_class_name_literal, ";");
 
                 type JavaType = jnix::jni::objects::AutoLocal<'env, 'borrow>;
 
-                #[allow(non_snake_case)]
-                fn into_java(self, env: &'borrow jnix::JnixEnv<'env>) -> Self::JavaType {
-                    #body
+                #conversion_method
+            }
+        }
+    }
+
+    pub fn generate_into_java_exception(self) -> TokenStream {
+        let type_name = self.type_name;
+
+        let impl_generics = self.generics.impl_generics(vec![]);
+        let type_generics = self.generics.type_generics();
+        let where_clause = self.generics.where_clause(vec![], vec![]);
+
+        let default_class_name = self.attributes.get_value("exception_class");
+        let class_name = match self.data {
+            TypeData::Enum(variants) => {
+                variants.generate_exception_class_name(default_class_name.as_ref())
+            }
+            TypeData::Struct(_) => {
+                let class_name = default_class_name
+                    .expect("Missing Java exception class")
+                    .value()
+                    .replace(".", "/");
+                let class_name_literal = LitStr::new(&class_name, Span::call_site());
+
+                quote! { #class_name_literal }
+            }
+        };
+
+        quote! {
+            impl #impl_generics jnix::IntoJavaException for #type_name #type_generics
+            #where_clause
+            {
+                fn exception_class_name(&self) -> std::borrow::Cow<'_, str> {
+                    std::borrow::Cow::Borrowed(#class_name)
+                }
+
+                fn exception_message(&self) -> String {
+                    std::string::ToString::to_string(self)
                 }
             }
         }
@@ -141,22 +234,57 @@ impl TypeData {
         }
     }
 
+    /// Generates the code to add the classes used by the type to the registry of derived classes.
+    pub fn generate_class_registration(
+        &self,
+        type_name_literal: &LitStr,
+        jni_class_name: &str,
+    ) -> TokenStream {
+        let class_names = self
+            .jni_class_names(jni_class_name)
+            .into_iter()
+            .map(|class_name| LitStr::new(&class_name, Span::call_site()));
+
+        quote! {
+            #(
+                jnix::inventory::submit! {
+                    jnix::DerivedClass::new(#type_name_literal, #class_names)
+                }
+            )*
+        }
+    }
+
+    fn jni_class_names(&self, jni_class_name: &str) -> Vec<String> {
+        let mut class_names = vec![jni_class_name.to_owned()];
+
+        if let TypeData::Enum(variants) = self {
+            class_names.extend(variants.variant_jni_class_names(jni_class_name));
+        }
+
+        class_names
+    }
+
     pub fn generate_from_java_body(
         self,
         jni_class_name_literal: &LitStr,
+        type_name_literal: &LitStr,
         class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         match self {
             TypeData::Enum(variants) => variants.generate_enum_from_java(
                 jni_class_name_literal,
+                type_name_literal,
                 class_name,
                 type_parameters,
+                mode,
             ),
             TypeData::Struct(fields) => fields.generate_struct_from_java(
                 jni_class_name_literal,
-                class_name,
+                type_name_literal,
                 type_parameters,
+                mode,
             ),
         }
     }
@@ -167,6 +295,7 @@ impl TypeData {
         type_name_literal: &LitStr,
         class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         match self {
             TypeData::Enum(variants) => variants.generate_enum_into_java(
@@ -174,12 +303,13 @@ impl TypeData {
                 type_name_literal,
                 class_name,
                 type_parameters,
+                mode,
             ),
             TypeData::Struct(fields) => fields.generate_struct_into_java(
                 jni_class_name_literal,
                 type_name_literal,
-                class_name,
                 type_parameters,
+                mode,
             ),
         }
     }
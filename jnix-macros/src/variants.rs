@@ -1,4 +1,4 @@
-use crate::{JnixAttributes, ParsedFields, TypeParameters};
+use crate::{ConversionMode, JnixAttributes, ParsedFields, TypeParameters};
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{punctuated::Punctuated, Ident, LitStr, Token, Variant};
@@ -35,14 +35,31 @@ impl ParsedVariants {
         }
     }
 
+    /// Returns the JNI names of the classes that represent the variants.
+    ///
+    /// Enum classes represent the variants as entries of the same class, so there are no variant
+    /// classes in that case.
+    pub fn variant_jni_class_names(&self, jni_class_name: &str) -> Vec<String> {
+        if self.enum_class {
+            vec![]
+        } else {
+            self.variants
+                .iter()
+                .map(|variant| format!("{}${}", jni_class_name, variant.name))
+                .collect()
+        }
+    }
+
     pub fn generate_enum_from_java(
         self,
         jni_class_name_literal: &LitStr,
+        type_name_literal: &LitStr,
         class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         let (class_binding, error_description, conversions) = if self.enum_class {
-            let class_binding = quote! { let class = env.get_class(#jni_class_name_literal); };
+            let class_binding = quote! { let class = env.try_get_class(#jni_class_name_literal)?; };
             let error_description = "Java enum class entry";
             let conversions =
                 self.generate_enum_class_from_java_conversions(jni_class_name_literal, class_name);
@@ -53,8 +70,10 @@ impl ParsedVariants {
             let error_description = "sub-class";
             let conversions = self.generate_sealed_class_from_java_conversions(
                 jni_class_name_literal,
+                type_name_literal,
                 class_name,
                 type_parameters,
+                mode,
             );
 
             (class_binding, error_description, conversions)
@@ -63,11 +82,17 @@ impl ParsedVariants {
         quote! {
             #class_binding
 
-            None
-                #( .or_else(|| { #conversions }) )*
-                .unwrap_or_else(|| panic!(
-                    concat!("Invalid ", #error_description, " of ", #jni_class_name_literal),
-                ))
+            #(
+                let variant: Option<Self> = { #conversions };
+
+                if let Some(variant) = variant {
+                    return Ok(variant);
+                }
+            )*
+
+            Err(jnix::JnixError::invalid_value(
+                concat!("Invalid ", #error_description, " of ", #jni_class_name_literal),
+            ))
         }
     }
 
@@ -77,6 +102,7 @@ impl ParsedVariants {
         type_name_literal: &LitStr,
         class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> TokenStream {
         let conversions = if self.enum_class {
             self.generate_enum_class_into_java_conversions(
@@ -88,8 +114,8 @@ impl ParsedVariants {
             self.generate_sealed_class_into_java_conversions(
                 jni_class_name_literal,
                 type_name_literal,
-                class_name,
                 type_parameters,
+                mode,
             )
         };
 
@@ -111,6 +137,31 @@ impl ParsedVariants {
         }
     }
 
+    pub fn generate_exception_class_name(
+        &self,
+        default_class_name: Option<&LitStr>,
+    ) -> TokenStream {
+        let class_names = self.variants.iter().map(|variant| {
+            let class_name = variant
+                .attributes
+                .get_value("exception_class")
+                .or_else(|| default_class_name.cloned())
+                .unwrap_or_else(|| {
+                    panic!("Missing Java exception class for variant {}", variant.name)
+                });
+
+            LitStr::new(&class_name.value().replace(".", "/"), Span::call_site())
+        });
+
+        let variants = self.variants.iter().map(|variant| &variant.name);
+
+        quote! {
+            match self {
+                #( Self::#variants { .. } => #class_names, )*
+            }
+        }
+    }
+
     fn generate_enum_class_from_java_conversions<'borrow, 'jni_class_name_literal, 'class_name>(
         &'borrow self,
         jni_class_name_literal: &'jni_class_name_literal LitStr,
@@ -128,31 +179,46 @@ impl ParsedVariants {
 
             let constructor = if variant.attributes.has_flag("deny") {
                 quote! {
-                    panic!(
+                    return Err(jnix::JnixError::invalid_value(
                         concat!("Can't create variant ", #variant_name_literal, " from Java type"),
-                    );
+                    ));
                 }
             } else {
                 quote! { Some(Self::#variant_name) }
             };
 
             quote! {
+                let field_id = env.get_cached_static_field_id(
+                    #jni_class_name_literal,
+                    #variant_name_literal,
+                    concat!("L", #jni_class_name_literal, ";"),
+                )?;
+                let field_type =
+                    jnix::jni::signature::JavaType::Object(String::new());
                 let candidate = env
-                    .get_static_field(
-                        &class,
-                        #variant_name_literal,
-                        concat!("L", #jni_class_name_literal, ";"),
-                    )
-                    .expect(concat!("Failed to get Java enum class variant ", #variant_class_name));
+                    .get_static_field_unchecked(&class, field_id, field_type)
+                    .map_err(|error| {
+                        env.convert_jni_error(error)
+                            .with_class(#jni_class_name_literal)
+                            .with_member(
+                                #variant_name_literal,
+                                concat!("L", #jni_class_name_literal, ";"),
+                            )
+                    })?;
 
                 match candidate {
                     jnix::jni::objects::JValue::Object(candidate) => {
-                        let found = env
-                            .is_same_object(jnix_source_object, candidate)
-                            .expect(concat!(
-                                "Failed to compare object to enum class entry of ",
-                                #variant_class_name,
-                            ));
+                        let comparison = env.is_same_object(jnix_source_object, candidate);
+
+                        let _ = env.delete_local_ref(candidate);
+
+                        let found = comparison
+                            .map_err(|error| {
+                                env.convert_jni_error(error).with_context(concat!(
+                                    "Comparing object to enum class entry of ",
+                                    #variant_class_name,
+                                ))
+                            })?;
 
                         if found {
                             #constructor
@@ -160,10 +226,12 @@ impl ParsedVariants {
                             None
                         }
                     }
-                    _ => panic!(concat!(
-                        "Invalid Java enum class variant retrieved for ",
-                        #variant_class_name,
-                    )),
+                    _ => {
+                        return Err(jnix::JnixError::invalid_value(concat!(
+                            "Invalid Java enum class variant retrieved for ",
+                            #variant_class_name,
+                        )));
+                    }
                 }
             }
         }))
@@ -172,20 +240,25 @@ impl ParsedVariants {
     fn generate_sealed_class_from_java_conversions<
         'borrow,
         'jni_class_name_literal,
+        'type_name_literal,
         'class_name,
         'type_parameters,
     >(
         &'borrow self,
         jni_class_name_literal: &'jni_class_name_literal LitStr,
+        type_name_literal: &'type_name_literal LitStr,
         class_name: &'class_name str,
         type_parameters: &'type_parameters TypeParameters,
+        mode: ConversionMode,
     ) -> Box<dyn Iterator<Item = TokenStream> + 'borrow>
     where
         'jni_class_name_literal: 'borrow,
+        'type_name_literal: 'borrow,
         'class_name: 'borrow,
         'type_parameters: 'borrow,
     {
         let jni_class_name = jni_class_name_literal.value();
+        let type_name = type_name_literal.value();
 
         Box::new(self.variants.iter().map(move |variant| {
             let variant_name_literal = LitStr::new(&variant.name.to_string(), variant.name.span());
@@ -196,30 +269,42 @@ impl ParsedVariants {
             let variant_jni_class_name_literal =
                 LitStr::new(&variant_jni_class_name, Span::call_site());
 
+            let variant_type_name = format!("{}::{}", type_name, variant.name);
+            let variant_type_name_literal = LitStr::new(&variant_type_name, Span::call_site());
+
             let constructor = if variant.attributes.has_flag("deny") {
                 quote! {
-                    panic!(
+                    return Err(jnix::JnixError::invalid_value(
                         concat!("Can't create variant ", #variant_name_literal, " from Java type"),
-                    );
+                    ));
                 }
             } else {
                 let fields_constructor = variant.fields.generate_enum_variant_from_java(
                     &variant_jni_class_name_literal,
-                    &variant_class_name,
+                    &variant_type_name_literal,
                     &variant.name,
                     type_parameters,
+                    mode,
                 );
 
-                quote! { Some({ #fields_constructor }) }
+                quote! {
+                    let variant = (|| -> Result<Self, jnix::JnixError> {
+                        #fields_constructor
+                    })()?;
+
+                    Some(variant)
+                }
             };
 
             quote! {
-                let candidate = env.get_class(#variant_jni_class_name_literal);
+                let candidate = env.try_get_class(#variant_jni_class_name_literal)?;
                 let found = env.is_instance_of(jnix_source_object, &candidate)
-                    .expect(concat!(
-                        "Failed to check if object is an instance of class ",
-                        #variant_class_name_literal,
-                    ));
+                    .map_err(|error| {
+                        env.convert_jni_error(error).with_context(concat!(
+                            "Checking if object is an instance of class ",
+                            #variant_class_name_literal,
+                        ))
+                    })?;
 
                 if found {
                     #constructor
@@ -243,26 +328,42 @@ impl ParsedVariants {
                     LitStr::new(&variant.name.to_string(), Span::call_site());
 
                 quote! {
-                    let class = env.get_class(#jni_class_name_literal);
-                    let variant = env.get_static_field(
-                        &class,
-                        #variant_name_literal,
-                        concat!("L", #jni_class_name_literal, ";"),
-                    ).expect(concat!("Failed to convert ",
-                        #type_name_literal, "::", #variant_name_literal,
-                        " Rust enum variant into ",
-                        #class_name,
-                        " Java enum class variant",
-                    ));
+                    let class = env.try_get_class(#jni_class_name_literal)?;
+                    let field_id = env
+                        .get_cached_static_field_id(
+                            #jni_class_name_literal,
+                            #variant_name_literal,
+                            concat!("L", #jni_class_name_literal, ";"),
+                        )
+                        .map_err(|error| {
+                            error.with_context(concat!(#type_name_literal, "::", #variant_name_literal))
+                        })?;
+                    let field_type =
+                        jnix::jni::signature::JavaType::Object(String::new());
+                    let variant = env
+                        .get_static_field_unchecked(&class, field_id, field_type)
+                        .map_err(|error| {
+                            env.convert_jni_error(error)
+                                .with_class(#jni_class_name_literal)
+                                .with_member(
+                                    #variant_name_literal,
+                                    concat!("L", #jni_class_name_literal, ";"),
+                                )
+                                .with_context(concat!(
+                                    #type_name_literal,
+                                    "::",
+                                    #variant_name_literal,
+                                ))
+                        })?;
 
                     match variant {
-                        jnix::jni::objects::JValue::Object(object) => env.auto_local(object),
-                        _ => panic!(concat!("Conversion from ",
+                        jnix::jni::objects::JValue::Object(object) => Ok(env.auto_local(object)),
+                        _ => Err(jnix::JnixError::invalid_value(concat!("Conversion from ",
                             #type_name_literal, "::", #variant_name_literal,
                             " Rust enum variant into ",
                             #class_name,
                             " Java object returned an invalid result.",
-                        )),
+                        ))),
                     }
                 }
             })
@@ -273,8 +374,8 @@ impl ParsedVariants {
         &self,
         jni_class_name_literal: &LitStr,
         type_name_literal: &LitStr,
-        class_name: &str,
         type_parameters: &TypeParameters,
+        mode: ConversionMode,
     ) -> Vec<TokenStream> {
         let jni_class_name = jni_class_name_literal.value();
         let type_name = type_name_literal.value();
@@ -282,8 +383,6 @@ impl ParsedVariants {
         self.variants
             .iter()
             .map(|variant| {
-                let variant_class_name = format!("{}.{}", class_name, variant.name);
-
                 let variant_jni_class_name = format!("{}${}", jni_class_name, variant.name);
                 let variant_jni_class_name_literal =
                     LitStr::new(&variant_jni_class_name, Span::call_site());
@@ -292,15 +391,14 @@ impl ParsedVariants {
                     self.generate_unit_variant_into_java_conversion(
                         &variant_jni_class_name,
                         variant_jni_class_name_literal,
-                        &variant_class_name,
                     )
                 } else {
                     self.generate_variant_with_fields_into_java_conversion(
                         variant_jni_class_name_literal,
                         &type_name,
-                        &variant_class_name,
                         type_parameters,
                         variant,
+                        mode,
                     )
                 }
             })
@@ -311,42 +409,33 @@ impl ParsedVariants {
         &self,
         variant_jni_class_name: &str,
         variant_jni_class_name_literal: LitStr,
-        variant_class_name: &str,
     ) -> TokenStream {
-        let variant_class_name_literal = LitStr::new(&variant_class_name, Span::call_site());
-
         let field_signature = format!("L{};", variant_jni_class_name);
         let field_signature_literal = LitStr::new(&field_signature, Span::call_site());
 
         quote! {
-            let class = env.get_class(#variant_jni_class_name_literal);
+            let class = env.try_get_class(#variant_jni_class_name_literal)?;
+            let jni_error = |error: jnix::jni::errors::Error| {
+                env.convert_jni_error(error)
+                    .with_class(#variant_jni_class_name_literal)
+                    .with_member("INSTANCE", #field_signature_literal)
+            };
 
-            let field_id = env
-                .get_static_field_id(&class, "INSTANCE", #field_signature_literal)
-                .expect(concat!(
-                    "Failed to get field ID for ",
-                    #variant_class_name_literal,
-                    ".INSTANCE"
-                ));
+            let field_id = env.get_cached_static_field_id(
+                #variant_jni_class_name_literal,
+                "INSTANCE",
+                #field_signature_literal,
+            )?;
 
             let field_type =
-                jnix::jni::signature::JavaType::Object(#variant_jni_class_name_literal.to_owned());
+                jnix::jni::signature::JavaType::Object(String::new());
 
             let instance = env
                 .get_static_field_unchecked(&class, field_id, field_type)
-                .expect(concat!(
-                    "Failed to retrieve ",
-                    #variant_class_name_literal,
-                    ".INSTANCE static field"
-                ))
-                .l()
-                .expect(concat!(
-                    "The ",
-                    #variant_class_name_literal,
-                    ".INSTANCE field is not an object"
-                ));
-
-            env.auto_local(instance)
+                .and_then(|instance| instance.l())
+                .map_err(jni_error)?;
+
+            Ok(env.auto_local(instance))
         }
     }
 
@@ -354,9 +443,9 @@ impl ParsedVariants {
         &self,
         variant_jni_class_name_literal: LitStr,
         type_name: &str,
-        variant_class_name: &str,
         type_parameters: &TypeParameters,
         variant: &ParsedVariant,
+        mode: ConversionMode,
     ) -> TokenStream {
         let variant_type_name = format!("{}::{}", type_name, variant.name);
         let variant_type_name_literal = LitStr::new(&variant_type_name, Span::call_site());
@@ -364,8 +453,8 @@ impl ParsedVariants {
         variant.fields.generate_enum_variant_into_java(
             &variant_jni_class_name_literal,
             &variant_type_name_literal,
-            &variant_class_name,
             type_parameters,
+            mode,
         )
     }
 }
@@ -0,0 +1,146 @@
+//! JNI signatures of arrays, built at compile time from the signatures of their elements.
+//!
+//! The signature of an array with elements of a generic type can't be built with
+//! [`crate::const_concat`], because the length of the concatenated bytes would depend on the
+//! generic type. It is built into a buffer with a fixed capacity instead. The smallest of a few
+//! buffer sizes that fits the signature is used, so that short signatures don't embed a large
+//! buffer for every element type.
+
+use std::marker::PhantomData;
+
+/// The maximum length of an array signature built by [`ArraySignature`].
+///
+/// Building the signature of an array whose element signature is longer than this (minus the `[`
+/// prefix) fails at compile time, with an error that includes the element signature.
+const MAX_LENGTH: usize = 512;
+
+/// The length of the element signature included in the error message when it is too long.
+const MESSAGE_SIGNATURE_LENGTH: usize = 128;
+
+const MESSAGE_PREFIX: &str = "JNI signature of the array elements is too long: ";
+const MESSAGE_SUFFIX: &str = "...";
+
+/// A type with a JNI signature, used as the element type of an [`ArraySignature`].
+pub(crate) trait ElementSignature {
+    /// The JNI signature of the element type.
+    const JNI_SIGNATURE: &'static str;
+}
+
+/// The JNI signature of an array with elements of type `E`.
+pub(crate) struct ArraySignature<E>(PhantomData<E>);
+
+impl<E: ElementSignature> ArraySignature<E> {
+    const LENGTH: usize = array_signature_length(E::JNI_SIGNATURE);
+
+    const SHORT_BYTES: [u8; 32] = array_signature_bytes(E::JNI_SIGNATURE);
+    const MEDIUM_BYTES: [u8; 128] = array_signature_bytes(E::JNI_SIGNATURE);
+    const LONG_BYTES: [u8; MAX_LENGTH] = array_signature_bytes(E::JNI_SIGNATURE);
+
+    /// The JNI signature of the array type, which is the element signature prefixed with `[`.
+    pub(crate) const JNI_SIGNATURE: &'static str = {
+        let bytes: &'static [u8] = if Self::LENGTH <= Self::SHORT_BYTES.len() {
+            &Self::SHORT_BYTES
+        } else if Self::LENGTH <= Self::MEDIUM_BYTES.len() {
+            &Self::MEDIUM_BYTES
+        } else {
+            &Self::LONG_BYTES
+        };
+
+        // SAFETY: The buffer fits the signature, so its first bytes are a `[` followed by all bytes
+        // of the element signature, which is a string, so they are valid UTF-8.
+        unsafe { utf8_prefix(bytes, Self::LENGTH) }
+    };
+}
+
+/// Returns the length of the array signature, failing if it's longer than [`MAX_LENGTH`].
+const fn array_signature_length(element_signature: &str) -> usize {
+    let length = element_signature.len() + 1;
+
+    if length > MAX_LENGTH {
+        let message = [0; MESSAGE_PREFIX.len() + MESSAGE_SIGNATURE_LENGTH + MESSAGE_SUFFIX.len()];
+        let (message, message_length) =
+            copy_into(message, 0, MESSAGE_PREFIX.as_bytes(), MESSAGE_PREFIX.len());
+        let (message, message_length) = copy_into(
+            message,
+            message_length,
+            element_signature.as_bytes(),
+            char_boundary_before(element_signature.as_bytes(), MESSAGE_SIGNATURE_LENGTH),
+        );
+        let (message, message_length) = copy_into(
+            message,
+            message_length,
+            MESSAGE_SUFFIX.as_bytes(),
+            MESSAGE_SUFFIX.len(),
+        );
+
+        // SAFETY: The message is made of complete strings and of a prefix of the element signature
+        // that ends at a character boundary, so it is valid UTF-8.
+        panic!("{}", unsafe { utf8_prefix(&message, message_length) });
+    }
+
+    length
+}
+
+/// Writes the array signature for the element signature into a buffer.
+///
+/// The signature is truncated if it doesn't fit, because the buffers of all sizes are evaluated,
+/// even the ones that aren't used.
+const fn array_signature_bytes<const CAPACITY: usize>(element_signature: &str) -> [u8; CAPACITY] {
+    let mut bytes = [0; CAPACITY];
+
+    bytes[0] = b'[';
+
+    copy_into(
+        bytes,
+        1,
+        element_signature.as_bytes(),
+        element_signature.len(),
+    )
+    .0
+}
+
+/// Copies up to `length` bytes into the buffer at the offset, stopping when the buffer is full.
+///
+/// Returns the buffer and the offset after the copied bytes.
+const fn copy_into<const CAPACITY: usize>(
+    mut buffer: [u8; CAPACITY],
+    offset: usize,
+    bytes: &[u8],
+    length: usize,
+) -> ([u8; CAPACITY], usize) {
+    let mut index = 0;
+
+    while index < length && index < bytes.len() && offset + index < CAPACITY {
+        buffer[offset + index] = bytes[index];
+        index += 1;
+    }
+
+    (buffer, offset + index)
+}
+
+/// Returns the largest length of a prefix of the UTF-8 bytes with at most `max_length` bytes that
+/// ends at a character boundary.
+const fn char_boundary_before(bytes: &[u8], max_length: usize) -> usize {
+    if bytes.len() <= max_length {
+        return bytes.len();
+    }
+
+    let mut length = max_length;
+
+    // Continuation bytes of multi-byte characters have the form `0b10xx_xxxx`.
+    while length > 0 && bytes[length] & 0xc0 == 0x80 {
+        length -= 1;
+    }
+
+    length
+}
+
+/// Returns the first `length` bytes as a string.
+///
+/// # Safety
+///
+/// The bytes must have at least `length` elements, and the first `length` bytes must be valid
+/// UTF-8.
+const unsafe fn utf8_prefix(bytes: &[u8], length: usize) -> &str {
+    std::str::from_utf8_unchecked(std::slice::from_raw_parts(bytes.as_ptr(), length))
+}
@@ -0,0 +1,171 @@
+//! Boxing of primitive values into their `java.lang` wrapper classes.
+//!
+//! Java collections and nullable values can only hold objects, so primitive values are boxed when
+//! they are stored in them and unboxed when they are read. The boxed class is selected from the
+//! JNI signature of the primitive type.
+
+use crate::{error::JniResultExt, JnixEnv, JnixError};
+use jni::{
+    objects::{AutoLocal, JObject, JValue},
+    signature::{JavaType, Primitive},
+};
+
+/// A `java.lang` class that wraps a primitive type.
+pub(crate) struct BoxedType {
+    class_name: &'static str,
+    jni_signature: &'static str,
+    primitive: Primitive,
+    value_of_signature: &'static str,
+    value_method: &'static str,
+    value_method_signature: &'static str,
+}
+
+macro_rules! boxed_type {
+    ($class_name:expr, $primitive:ident, $primitive_signature:expr, $value_method:expr) => {
+        BoxedType {
+            class_name: concat!("java/lang/", $class_name),
+            jni_signature: concat!("Ljava/lang/", $class_name, ";"),
+            primitive: Primitive::$primitive,
+            value_of_signature: concat!(
+                "(",
+                $primitive_signature,
+                ")Ljava/lang/",
+                $class_name,
+                ";"
+            ),
+            value_method: $value_method,
+            value_method_signature: concat!("()", $primitive_signature),
+        }
+    };
+}
+
+const BOOLEAN: BoxedType = boxed_type!("Boolean", Boolean, "Z", "booleanValue");
+const BYTE: BoxedType = boxed_type!("Byte", Byte, "B", "byteValue");
+const CHARACTER: BoxedType = boxed_type!("Character", Char, "C", "charValue");
+const SHORT: BoxedType = boxed_type!("Short", Short, "S", "shortValue");
+const INTEGER: BoxedType = boxed_type!("Integer", Int, "I", "intValue");
+const LONG: BoxedType = boxed_type!("Long", Long, "J", "longValue");
+const FLOAT: BoxedType = boxed_type!("Float", Float, "F", "floatValue");
+const DOUBLE: BoxedType = boxed_type!("Double", Double, "D", "doubleValue");
+
+/// Returns the boxed class for a primitive JNI signature, or `None` if the signature isn't of a
+/// primitive type.
+const fn boxed_type(jni_signature: &str) -> Option<&'static BoxedType> {
+    let signature = jni_signature.as_bytes();
+
+    if signature.len() != 1 {
+        return None;
+    }
+
+    match signature[0] {
+        b'Z' => Some(&BOOLEAN),
+        b'B' => Some(&BYTE),
+        b'C' => Some(&CHARACTER),
+        b'S' => Some(&SHORT),
+        b'I' => Some(&INTEGER),
+        b'J' => Some(&LONG),
+        b'F' => Some(&FLOAT),
+        b'D' => Some(&DOUBLE),
+        _ => None,
+    }
+}
+
+/// Returns the boxed class for a primitive value, or `None` if the value is an object.
+fn boxed_type_of(value: &JValue) -> Option<&'static BoxedType> {
+    match value {
+        JValue::Bool(_) => Some(&BOOLEAN),
+        JValue::Byte(_) => Some(&BYTE),
+        JValue::Char(_) => Some(&CHARACTER),
+        JValue::Short(_) => Some(&SHORT),
+        JValue::Int(_) => Some(&INTEGER),
+        JValue::Long(_) => Some(&LONG),
+        JValue::Float(_) => Some(&FLOAT),
+        JValue::Double(_) => Some(&DOUBLE),
+        JValue::Object(_) | JValue::Void => None,
+    }
+}
+
+/// Returns the JNI signature of the boxed class of a primitive type.
+///
+/// Signatures of other types are returned unchanged.
+pub(crate) const fn boxed_jni_signature(jni_signature: &'static str) -> &'static str {
+    match boxed_type(jni_signature) {
+        Some(boxed_type) => boxed_type.jni_signature,
+        None => jni_signature,
+    }
+}
+
+/// Boxes a primitive value into a new object of its wrapper class.
+pub(crate) fn box_primitive<'borrow, 'env>(
+    env: &'borrow JnixEnv<'env>,
+    value: JValue,
+) -> Result<AutoLocal<'env, 'borrow>, JnixError> {
+    let boxed_type = boxed_type_of(&value)
+        .ok_or_else(|| JnixError::invalid_value("Can't box a value that isn't a primitive"))?;
+
+    let object = env
+        .call_cached_static_method(
+            boxed_type.class_name,
+            "valueOf",
+            boxed_type.value_of_signature,
+            JavaType::Object(boxed_type.class_name.to_owned()),
+            &[value],
+        )?
+        .l()
+        .for_member(
+            env,
+            boxed_type.class_name,
+            "valueOf",
+            boxed_type.value_of_signature,
+        )?;
+
+    Ok(env.auto_local(object))
+}
+
+/// Runs a closure with a value as an object, boxing it first if it's a primitive.
+///
+/// The boxed object is deleted after the closure returns.
+pub(crate) fn with_boxed<T>(
+    env: &JnixEnv,
+    value: JValue,
+    operation: impl FnOnce(JObject) -> Result<T, JnixError>,
+) -> Result<T, JnixError> {
+    match value {
+        JValue::Object(object) => operation(object),
+        primitive => operation(box_primitive(env, primitive)?.as_obj()),
+    }
+}
+
+/// Unboxes an object into a value of the primitive type with the specified JNI signature.
+///
+/// If the signature isn't of a primitive type, the object is returned unchanged.
+pub(crate) fn unbox<'env, 'sub_env>(
+    env: &JnixEnv<'env>,
+    object: JObject<'sub_env>,
+    jni_signature: &str,
+) -> Result<JValue<'sub_env>, JnixError>
+where
+    'env: 'sub_env,
+{
+    let boxed_type = match boxed_type(jni_signature) {
+        Some(boxed_type) => boxed_type,
+        None => return Ok(JValue::Object(object)),
+    };
+
+    if object.is_null() {
+        return Err(JnixError::invalid_value(format!(
+            "Can't unbox null reference into a primitive with signature {}",
+            jni_signature
+        ))
+        .with_class(boxed_type.class_name));
+    }
+
+    env.call_cached_method(
+        object,
+        boxed_type.class_name,
+        boxed_type.value_method,
+        boxed_type.value_method_signature,
+        JavaType::Primitive(boxed_type.primitive),
+        &[],
+    )
+}
@@ -0,0 +1,34 @@
+//! Helpers to call methods and read fields without parsing their JNI signatures.
+//!
+//! The derive macros use these so that the getters and static fields used by the generated
+//! conversions don't allocate a parsed `JavaType` on every conversion.
+
+use jni::signature::{JavaType, Primitive};
+
+/// Returns the type used to call a method or read a field with the JNI signature through the
+/// `unchecked` functions of `JNIEnv`.
+///
+/// Those functions only use the kind of the type to select the JNI function to call, so the class
+/// name of object and array types is left empty, which doesn't allocate.
+pub fn java_type_for_call(jni_signature: &str) -> Result<JavaType, jni::errors::Error> {
+    let primitive = match jni_signature {
+        "Z" => Primitive::Boolean,
+        "B" => Primitive::Byte,
+        "C" => Primitive::Char,
+        "S" => Primitive::Short,
+        "I" => Primitive::Int,
+        "J" => Primitive::Long,
+        "F" => Primitive::Float,
+        "D" => Primitive::Double,
+        "V" => Primitive::Void,
+        _ if is_reference_signature(jni_signature) => return Ok(JavaType::Object(String::new())),
+        _ => return jni_signature.parse(),
+    };
+
+    Ok(JavaType::Primitive(primitive))
+}
+
+fn is_reference_signature(jni_signature: &str) -> bool {
+    (jni_signature.starts_with('L') && jni_signature.ends_with(';'))
+        || (jni_signature.starts_with('[') && jni_signature.len() > 1)
+}
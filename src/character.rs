@@ -0,0 +1,18 @@
+//! Wrappers that select how a Rust `char` is converted.
+//!
+//! By default, a `char` is converted to a Java `char`, which fails for characters outside the Basic
+//! Multilingual Plane because they need two UTF-16 code units. The wrappers in this module can
+//! represent any character. Derived conversions can also select the representation for a field
+//! with the `#[jnix(char = "...")]` attribute, using `"char"`, `"string"` or `"code_point"`.
+
+/// A character converted to a `java.lang.String` that contains only that character.
+///
+/// Converting from Java fails if the string doesn't contain exactly one character.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CharString(pub char);
+
+/// A character converted to a Java `int` with its Unicode code point.
+///
+/// Converting from Java fails if the value isn't a valid Unicode scalar value.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CodePoint(pub char);
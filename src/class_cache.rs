@@ -0,0 +1,136 @@
+use crate::JnixError;
+use jni::{objects::GlobalRef, sys, JavaVM};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// The class caches of all Java VMs.
+///
+/// Classes are looked up much more often than they are loaded, so a read-write lock is used to
+/// allow concurrent lookups. The lock is never held while a class is loaded.
+static CLASS_CACHES: Lazy<RwLock<HashMap<VmKey, ClassCache>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// How a cached class was loaded.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ClassSource {
+    /// The class was found with the JNI `FindClass` function, which uses the class loader
+    /// associated with the calling native code, or the system class loader on threads attached
+    /// from native code.
+    FindClass,
+
+    /// The class was loaded with `ClassLoader.loadClass` using the application class loader set
+    /// with [`JnixEnv::set_class_loader`][crate::JnixEnv::set_class_loader].
+    ClassLoader,
+}
+
+/// The result of preloading classes with
+/// [`JnixEnv::try_preload_classes`][crate::JnixEnv::try_preload_classes].
+#[derive(Debug, Default)]
+pub struct PreloadReport {
+    loaded: Vec<String>,
+    failures: Vec<(String, JnixError)>,
+}
+
+impl PreloadReport {
+    pub(crate) fn add_loaded(&mut self, class_name: String) {
+        self.loaded.push(class_name);
+    }
+
+    pub(crate) fn add_failure(&mut self, class_name: String, error: JnixError) {
+        self.failures.push((class_name, error));
+    }
+
+    /// Returns the names of the classes that were loaded or that were already in the class cache.
+    pub fn loaded(&self) -> &[String] {
+        &self.loaded
+    }
+
+    /// Returns the names of the classes that failed to load, together with the errors.
+    ///
+    /// If a class wasn't found, the error contains the `NoClassDefFoundError` or
+    /// `ClassNotFoundException` that was thrown.
+    pub fn failures(&self) -> &[(String, JnixError)] {
+        &self.failures
+    }
+
+    /// Checks if all classes were loaded.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Display for PreloadReport {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "Loaded {} class(es), failed to load {} class(es)",
+            self.loaded.len(),
+            self.failures.len()
+        )?;
+
+        for (class_name, error) in &self.failures {
+            write!(formatter, "\n  - {}: {}", class_name, error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Identifies the Java VM that owns the cached classes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct VmKey(usize);
+
+impl VmKey {
+    pub fn new(vm: *mut sys::JavaVM) -> Self {
+        VmKey(vm as usize)
+    }
+}
+
+impl From<&JavaVM> for VmKey {
+    fn from(vm: &JavaVM) -> Self {
+        VmKey::new(vm.get_java_vm_pointer())
+    }
+}
+
+/// A class stored in the class cache.
+#[derive(Clone)]
+pub(crate) struct CachedClass {
+    pub class: GlobalRef,
+    pub source: ClassSource,
+}
+
+/// The loaded classes of a Java VM, and the application class loader used to load classes that
+/// `FindClass` can't find.
+#[derive(Default)]
+pub(crate) struct ClassCache {
+    pub classes: HashMap<String, CachedClass>,
+    pub class_loader: Option<GlobalRef>,
+}
+
+/// Runs a closure with shared access to the class cache of a Java VM, or with `None` if the Java
+/// VM has no cache yet.
+pub(crate) fn read_class_cache<T>(
+    vm: VmKey,
+    operation: impl FnOnce(Option<&ClassCache>) -> T,
+) -> T {
+    operation(CLASS_CACHES.read().get(&vm))
+}
+
+/// Runs a closure with exclusive access to the class cache of a Java VM, creating an empty cache
+/// if there isn't one.
+///
+/// The closure should be short, because it blocks all lookups in the class cache while it runs.
+pub(crate) fn write_class_cache<T>(vm: VmKey, operation: impl FnOnce(&mut ClassCache) -> T) -> T {
+    operation(CLASS_CACHES.write().entry(vm).or_default())
+}
+
+/// Removes the class cache of a Java VM.
+///
+/// The cache is returned so that its global references can be deleted after the lock is released.
+pub(crate) fn remove_class_cache(vm: VmKey) -> Option<ClassCache> {
+    CLASS_CACHES.write().remove(&vm)
+}
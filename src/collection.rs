@@ -0,0 +1,29 @@
+//! Wrappers that select how Rust sequences are converted.
+//!
+//! By default, `Vec<T>`, `&[T]`, `Box<[T]>` and `[T; N]` of primitive types are converted to Java
+//! primitive arrays, like `int[]` for `i32` elements. `Vec<T>` of other types is converted to a
+//! `java.util.ArrayList`, and `[T; N]` of other types is converted to a Java object array.
+//!
+//! The wrappers in this module select a different representation for a single value. Derived
+//! conversions can also select the representation for a field with the
+//! `#[jnix(collection = "...")]` attribute, using `"array"` or `"list"`.
+
+/// A sequence converted to a `java.util.ArrayList`, boxing primitive elements into their
+/// `java.lang` wrapper classes.
+///
+/// This is only needed for sequences of primitive types, which are converted to Java primitive
+/// arrays by default.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct JavaList<T>(pub T);
+
+/// A sequence converted to a Java array.
+///
+/// Elements of primitive types are stored in a Java primitive array, like `int[]` for `i32`
+/// elements. Elements of other types are stored in a Java object array of the class of the
+/// element type, like `String[]` for `String` elements. Converting from Java reads any object
+/// array, including an `Object[]`, as long as its elements can be converted.
+///
+/// This is only needed for `Vec<T>` and `Box<[T]>` of types that aren't primitives, which are
+/// converted to a `java.util.ArrayList` by default.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct JavaArray<T>(pub T);
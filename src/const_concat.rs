@@ -0,0 +1,78 @@
+//! Helpers to concatenate string constants at compile time.
+//!
+//! The derive macros use these to build the JNI signatures of constructors and getters from the
+//! `JNI_SIGNATURE` constants of the field types, which `concat!` doesn't accept.
+
+/// Returns the total length in bytes of the concatenated parts.
+pub const fn concatenated_length(parts: &[&str]) -> usize {
+    let mut length = 0;
+    let mut index = 0;
+
+    while index < parts.len() {
+        length += parts[index].len();
+        index += 1;
+    }
+
+    length
+}
+
+/// The bytes of concatenated strings.
+///
+/// The bytes are always valid UTF-8, because they can only be created by [`concatenate`] from
+/// complete strings.
+pub struct Concatenated<const LENGTH: usize>([u8; LENGTH]);
+
+/// Concatenates the parts into a byte array.
+///
+/// The length of the array must be the value returned by [`concatenated_length`].
+pub const fn concatenate<const LENGTH: usize>(parts: &[&str]) -> Concatenated<LENGTH> {
+    let mut output = [0; LENGTH];
+    let mut output_index = 0;
+    let mut part_index = 0;
+
+    while part_index < parts.len() {
+        let part = parts[part_index].as_bytes();
+        let mut byte_index = 0;
+
+        while byte_index < part.len() {
+            output[output_index] = part[byte_index];
+            output_index += 1;
+            byte_index += 1;
+        }
+
+        part_index += 1;
+    }
+
+    assert!(output_index == LENGTH, "Invalid concatenated string length");
+
+    Concatenated(output)
+}
+
+/// Converts the concatenated bytes back into a string.
+pub const fn as_str<const LENGTH: usize>(concatenated: &Concatenated<LENGTH>) -> &str {
+    // SAFETY: `concatenate` fills all bytes by copying complete strings, and a concatenation of
+    // valid UTF-8 sequences is also valid UTF-8.
+    unsafe { std::str::from_utf8_unchecked(&concatenated.0) }
+}
+
+/// Checks if two strings are equal.
+pub const fn equals(first: &str, second: &str) -> bool {
+    let first = first.as_bytes();
+    let second = second.as_bytes();
+
+    if first.len() != second.len() {
+        return false;
+    }
+
+    let mut index = 0;
+
+    while index < first.len() {
+        if first[index] != second[index] {
+            return false;
+        }
+
+        index += 1;
+    }
+
+    true
+}
@@ -0,0 +1,47 @@
+use std::collections::BTreeSet;
+
+/// A Java class used by a Rust type that derives one of the conversion traits.
+///
+/// The derive macros add the classes they use to a global registry, so that they can all be
+/// loaded with [`JnixEnv::preload_all_derived_classes`].
+///
+/// [`JnixEnv::preload_all_derived_classes`]: crate::JnixEnv::preload_all_derived_classes
+pub struct DerivedClass {
+    type_name: &'static str,
+    class_name: &'static str,
+}
+
+inventory::collect!(DerivedClass);
+
+impl DerivedClass {
+    /// Creates a description of a class used by a derived type.
+    ///
+    /// The class name must be in the JNI format (e.g., `my/package/MyClass$Variant`).
+    pub const fn new(type_name: &'static str, class_name: &'static str) -> Self {
+        DerivedClass {
+            type_name,
+            class_name,
+        }
+    }
+
+    /// Returns the name of the Rust type that uses the class.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the name of the Java class, in the JNI format.
+    pub fn class_name(&self) -> &'static str {
+        self.class_name
+    }
+}
+
+/// Returns the names of all classes in the global registry, without duplicates.
+///
+/// A class is usually registered more than once, because each derived trait registers the classes
+/// it uses.
+pub(crate) fn derived_class_names() -> BTreeSet<&'static str> {
+    inventory::iter::<DerivedClass>
+        .into_iter()
+        .map(DerivedClass::class_name)
+        .collect()
+}
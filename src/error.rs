@@ -0,0 +1,227 @@
+use crate::{JavaException, JnixEnv};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// An error that occurred while converting between a Rust type and a Java type.
+///
+/// Besides the underlying cause of the error, it also contains information about the Java class,
+/// the class member and the member's JNI signature that were being used when the error happened,
+/// if that information is available. Conversions of nested types can also add context to the error,
+/// like which field of a Rust type was being converted.
+#[derive(Debug)]
+pub struct JnixError {
+    inner: Box<ErrorInner>,
+}
+
+#[derive(Debug)]
+struct ErrorInner {
+    kind: JnixErrorKind,
+    class: Option<Cow<'static, str>>,
+    member: Option<Cow<'static, str>>,
+    signature: Option<Cow<'static, str>>,
+    context: Vec<Cow<'static, str>>,
+}
+
+/// The underlying cause of a [`JnixError`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JnixErrorKind {
+    /// A JNI operation failed.
+    Jni(jni::errors::Error),
+
+    /// A Java exception was thrown.
+    JavaException(JavaException),
+
+    /// A Java value could not be converted because it isn't valid for the target type.
+    InvalidValue(Cow<'static, str>),
+}
+
+impl JnixError {
+    /// Creates a new error without any information about where it happened.
+    pub fn new(kind: impl Into<JnixErrorKind>) -> Self {
+        JnixError {
+            inner: Box::new(ErrorInner {
+                kind: kind.into(),
+                class: None,
+                member: None,
+                signature: None,
+                context: Vec::new(),
+            }),
+        }
+    }
+
+    /// Creates a new error caused by an invalid value.
+    pub fn invalid_value(description: impl Into<Cow<'static, str>>) -> Self {
+        JnixError::new(JnixErrorKind::InvalidValue(description.into()))
+    }
+
+    /// Sets the Java class that was being used when the error happened.
+    pub fn with_class(mut self, class: impl Into<Cow<'static, str>>) -> Self {
+        self.inner.class = Some(class.into());
+        self
+    }
+
+    /// Sets the Java class member and its JNI signature that were being used when the error
+    /// happened.
+    pub fn with_member(
+        mut self,
+        member: impl Into<Cow<'static, str>>,
+        signature: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.inner.member = Some(member.into());
+        self.inner.signature = Some(signature.into());
+        self
+    }
+
+    /// Adds a description of what was being converted when the error happened.
+    ///
+    /// This is used to add information about the outer types when a conversion of an inner type
+    /// fails, so the context should be added from the innermost to the outermost type.
+    pub fn with_context(mut self, context: impl Into<Cow<'static, str>>) -> Self {
+        self.inner.context.push(context.into());
+        self
+    }
+
+    /// Returns the underlying cause of the error.
+    pub fn kind(&self) -> &JnixErrorKind {
+        &self.inner.kind
+    }
+
+    /// Returns the Java exception that caused the error, if the error was caused by one.
+    pub fn java_exception(&self) -> Option<&JavaException> {
+        match &self.inner.kind {
+            JnixErrorKind::JavaException(exception) => Some(exception),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the Java class that was being used when the error happened.
+    pub fn class(&self) -> Option<&str> {
+        self.inner.class.as_deref()
+    }
+
+    /// Returns the name of the Java class member that was being used when the error happened.
+    pub fn member(&self) -> Option<&str> {
+        self.inner.member.as_deref()
+    }
+
+    /// Returns the JNI signature of the Java class member that was being used when the error
+    /// happened.
+    pub fn signature(&self) -> Option<&str> {
+        self.inner.signature.as_deref()
+    }
+
+    /// Returns the descriptions of what was being converted when the error happened, from the
+    /// outermost to the innermost type.
+    pub fn context(&self) -> impl Iterator<Item = &str> + '_ {
+        self.inner
+            .context
+            .iter()
+            .rev()
+            .map(|context| context.as_ref())
+    }
+}
+
+impl From<jni::errors::Error> for JnixError {
+    fn from(error: jni::errors::Error) -> Self {
+        JnixError::new(error)
+    }
+}
+
+impl From<jni::errors::Error> for JnixErrorKind {
+    fn from(error: jni::errors::Error) -> Self {
+        JnixErrorKind::Jni(error)
+    }
+}
+
+impl Display for JnixError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        for context in self.context() {
+            write!(formatter, "{}: ", context)?;
+        }
+
+        match (&self.inner.class, &self.inner.member) {
+            (Some(class), Some(member)) => write!(
+                formatter,
+                "{}.{}{}: ",
+                class,
+                member,
+                self.inner.signature.as_deref().unwrap_or("")
+            )?,
+            (Some(class), None) => write!(formatter, "{}: ", class)?,
+            (None, Some(member)) => write!(
+                formatter,
+                "{}{}: ",
+                member,
+                self.inner.signature.as_deref().unwrap_or("")
+            )?,
+            (None, None) => {}
+        }
+
+        write!(formatter, "{}", self.inner.kind)
+    }
+}
+
+impl Display for JnixErrorKind {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            JnixErrorKind::Jni(error) => write!(formatter, "JNI operation failed: {}", error),
+            JnixErrorKind::JavaException(exception) => {
+                write!(formatter, "Java exception was thrown: {}", exception)
+            }
+            JnixErrorKind::InvalidValue(description) => {
+                write!(formatter, "Invalid value: {}", description)
+            }
+        }
+    }
+}
+
+impl Error for JnixError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.inner.kind {
+            JnixErrorKind::Jni(error) => Some(error),
+            JnixErrorKind::JavaException(exception) => Some(exception),
+            JnixErrorKind::InvalidValue(_) => None,
+        }
+    }
+}
+
+/// Helper trait to annotate the errors of low-level JNI operations.
+///
+/// Any pending Java exception is captured as the cause of the error.
+pub(crate) trait JniResultExt<T> {
+    /// Converts the error into a [`JnixError`] that happened while using a Java class.
+    fn for_class(self, env: &JnixEnv, class: impl Into<Cow<'static, str>>) -> Result<T, JnixError>;
+
+    /// Converts the error into a [`JnixError`] that happened while using a Java class member.
+    fn for_member(
+        self,
+        env: &JnixEnv,
+        class: impl Into<Cow<'static, str>>,
+        member: impl Into<Cow<'static, str>>,
+        signature: impl Into<Cow<'static, str>>,
+    ) -> Result<T, JnixError>;
+}
+
+impl<T> JniResultExt<T> for jni::errors::Result<T> {
+    fn for_class(self, env: &JnixEnv, class: impl Into<Cow<'static, str>>) -> Result<T, JnixError> {
+        self.map_err(|error| env.convert_jni_error(error).with_class(class))
+    }
+
+    fn for_member(
+        self,
+        env: &JnixEnv,
+        class: impl Into<Cow<'static, str>>,
+        member: impl Into<Cow<'static, str>>,
+        signature: impl Into<Cow<'static, str>>,
+    ) -> Result<T, JnixError> {
+        self.map_err(|error| {
+            env.convert_jni_error(error)
+                .with_class(class)
+                .with_member(member, signature)
+        })
+    }
+}
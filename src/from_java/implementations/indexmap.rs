@@ -0,0 +1,39 @@
+use super::std::{element_from_java, try_element_from_java, try_map_from_java};
+use crate::{FromJava, JnixEnv, JnixError, TryFromJava};
+use indexmap::IndexMap;
+use jni::objects::{JObject, JValue};
+use std::hash::Hash;
+
+impl<'env, 'sub_env, K, V> FromJava<'env, JObject<'sub_env>> for IndexMap<K, V>
+where
+    'env: 'sub_env,
+    K: FromJava<'env, JValue<'sub_env>> + Eq + Hash,
+    V: FromJava<'env, JValue<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        try_map_from_java(env, source, |key, value| {
+            Ok((element_from_java(env, key)?, element_from_java(env, value)?))
+        })
+        .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'env, 'sub_env, K, V> TryFromJava<'env, JObject<'sub_env>> for IndexMap<K, V>
+where
+    'env: 'sub_env,
+    K: TryFromJava<'env, JValue<'sub_env>> + Eq + Hash,
+    V: TryFromJava<'env, JValue<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        try_map_from_java(env, source, |key, value| {
+            Ok((
+                try_element_from_java(env, key)?,
+                try_element_from_java(env, value)?,
+            ))
+        })
+    }
+}
@@ -1 +1,3 @@
+#[cfg(feature = "indexmap")]
+mod indexmap;
 mod std;
@@ -0,0 +1,483 @@
+use crate::{
+    array_signature::{ArraySignature, ElementSignature},
+    error::JniResultExt,
+    FromJava, JavaArray, JnixEnv, JnixError, TryFromJava,
+};
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jarray, jboolean, jbyte, jchar, jdouble, jfloat, jint, jlong, jshort, jsize, JNI_FALSE},
+};
+use std::{convert::TryFrom, marker::PhantomData};
+
+fn length_mismatch(
+    length: usize,
+    expected_length: usize,
+    jni_signature: &'static str,
+) -> JnixError {
+    JnixError::invalid_value(format!(
+        "Java array has {} elements, but the Rust array has {}",
+        length, expected_length
+    ))
+    .with_class(jni_signature)
+}
+
+/// Returns the length of a Java array, failing if it isn't the expected length.
+///
+/// The source object is checked to be an instance of the array class first, because the JNI array
+/// functions don't check it.
+fn array_length(
+    env: &JnixEnv,
+    source: JObject,
+    array_class_name: &str,
+    jni_signature: &'static str,
+    expected_length: Option<usize>,
+) -> Result<usize, JnixError> {
+    if source.is_null() {
+        return Err(
+            JnixError::invalid_value("Java array reference is null").with_class(jni_signature)
+        );
+    }
+
+    let array_class = env.try_get_class(array_class_name)?;
+    let is_array = env
+        .is_instance_of(source, JClass::from(array_class.as_obj()))
+        .for_class(env, jni_signature)?;
+
+    if !is_array {
+        return Err(JnixError::invalid_value(format!(
+            "Java object is not an array with signature {}",
+            jni_signature
+        ))
+        .with_class(jni_signature));
+    }
+
+    let length = env
+        .get_array_length(source.into_inner())
+        .for_class(env, jni_signature)? as usize;
+
+    match expected_length {
+        Some(expected_length) if length != expected_length => {
+            Err(length_mismatch(length, expected_length, jni_signature))
+        }
+        _ => Ok(length),
+    }
+}
+
+/// Converts the elements read from a Java array into a Rust array.
+fn into_array<T, const N: usize>(
+    elements: Vec<T>,
+    jni_signature: &'static str,
+) -> Result<[T; N], JnixError> {
+    <[T; N]>::try_from(elements)
+        .map_err(|elements| length_mismatch(elements.len(), N, jni_signature))
+}
+
+/// Copies all elements of a Java primitive array into a vector.
+fn read_primitive_array<J>(
+    env: &JnixEnv,
+    source: JObject,
+    jni_signature: &'static str,
+    expected_length: Option<usize>,
+    get_array_region: impl FnOnce(jarray, &mut [J]) -> jni::errors::Result<()>,
+) -> Result<Vec<J>, JnixError>
+where
+    J: Copy + Default,
+{
+    let length = array_length(env, source, jni_signature, jni_signature, expected_length)?;
+    let mut elements = vec![J::default(); length];
+
+    get_array_region(source.into_inner(), &mut elements).for_class(env, jni_signature)?;
+
+    Ok(elements)
+}
+
+/// A primitive type that is read from a Java primitive array.
+trait PrimitiveArrayElement: Sized {
+    /// Reads all elements of a Java primitive array, failing if it isn't the expected length.
+    fn try_read_array(
+        env: &JnixEnv,
+        source: JObject,
+        expected_length: Option<usize>,
+    ) -> Result<Vec<Self>, JnixError>;
+}
+
+macro_rules! impl_from_java_for_primitive_array {
+    (
+        $element_type:ty,
+        $java_element_type:ty,
+        $signature:expr,
+        $get_array_region:ident,
+        |$env:ident, $elements:ident| $conversion:expr
+    ) => {
+        impl PrimitiveArrayElement for $element_type {
+            fn try_read_array(
+                env: &JnixEnv,
+                source: JObject,
+                expected_length: Option<usize>,
+            ) -> Result<Vec<Self>, JnixError> {
+                let $elements: Vec<$java_element_type> = read_primitive_array(
+                    env,
+                    source,
+                    $signature,
+                    expected_length,
+                    |array, buffer| env.$get_array_region(array, 0, buffer),
+                )?;
+                let $env = env;
+
+                Ok($conversion)
+            }
+        }
+
+        impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for Vec<$element_type>
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn try_from_java(
+                env: &JnixEnv<'env>,
+                source: JObject<'sub_env>,
+            ) -> Result<Self, JnixError> {
+                <$element_type>::try_read_array(env, source, None)
+            }
+        }
+
+        impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for Vec<$element_type>
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                <Vec<$element_type>>::try_from_java(env, source)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+
+        impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for Box<[$element_type]>
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn try_from_java(
+                env: &JnixEnv<'env>,
+                source: JObject<'sub_env>,
+            ) -> Result<Self, JnixError> {
+                <$element_type>::try_read_array(env, source, None).map(Vec::into_boxed_slice)
+            }
+        }
+
+        impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for Box<[$element_type]>
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                <Box<[$element_type]>>::try_from_java(env, source)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+
+        impl_from_java_for_primitive_array!(
+            @wrapped JavaArray<Vec<$element_type>>,
+            Vec<$element_type>,
+            $signature
+        );
+        impl_from_java_for_primitive_array!(
+            @wrapped JavaArray<Box<[$element_type]>>,
+            Box<[$element_type]>,
+            $signature
+        );
+
+        impl<'env, 'sub_env, const N: usize> TryFromJava<'env, JObject<'sub_env>>
+            for [$element_type; N]
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn try_from_java(
+                env: &JnixEnv<'env>,
+                source: JObject<'sub_env>,
+            ) -> Result<Self, JnixError> {
+                let elements = <$element_type>::try_read_array(env, source, Some(N))?;
+
+                into_array(elements, $signature)
+            }
+        }
+
+        impl<'env, 'sub_env, const N: usize> FromJava<'env, JObject<'sub_env>>
+            for [$element_type; N]
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                <[$element_type; N]>::try_from_java(env, source)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+    };
+
+    (@wrapped $rust_type:ty, $wrapped_type:ty, $signature:expr) => {
+        impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for $rust_type
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn try_from_java(
+                env: &JnixEnv<'env>,
+                source: JObject<'sub_env>,
+            ) -> Result<Self, JnixError> {
+                <$wrapped_type>::try_from_java(env, source).map(JavaArray)
+            }
+        }
+
+        impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for $rust_type
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                JavaArray(<$wrapped_type>::from_java(env, source))
+            }
+        }
+    };
+}
+
+impl_from_java_for_primitive_array!(
+    bool,
+    jboolean,
+    "[Z",
+    get_boolean_array_region,
+    |_env, elements| {
+        elements
+            .into_iter()
+            .map(|element| element != JNI_FALSE)
+            .collect()
+    }
+);
+impl_from_java_for_primitive_array!(i8, jbyte, "[B", get_byte_array_region, |_env, elements| {
+    elements
+});
+impl_from_java_for_primitive_array!(
+    i16,
+    jshort,
+    "[S",
+    get_short_array_region,
+    |_env, elements| elements
+);
+impl_from_java_for_primitive_array!(i32, jint, "[I", get_int_array_region, |_env, elements| {
+    elements
+});
+impl_from_java_for_primitive_array!(i64, jlong, "[J", get_long_array_region, |_env, elements| {
+    elements
+});
+impl_from_java_for_primitive_array!(
+    f32,
+    jfloat,
+    "[F",
+    get_float_array_region,
+    |_env, elements| elements
+);
+impl_from_java_for_primitive_array!(
+    f64,
+    jdouble,
+    "[D",
+    get_double_array_region,
+    |_env, elements| elements
+);
+impl_from_java_for_primitive_array!(u8, jbyte, "[B", get_byte_array_region, |_env, elements| {
+    elements.into_iter().map(|element| element as u8).collect()
+});
+impl_from_java_for_primitive_array!(u16, jchar, "[C", get_char_array_region, |_env, elements| {
+    elements
+});
+impl_from_java_for_primitive_array!(u32, jint, "[I", get_int_array_region, |_env, elements| {
+    elements.into_iter().map(|element| element as u32).collect()
+});
+impl_from_java_for_primitive_array!(u64, jlong, "[J", get_long_array_region, |_env, elements| {
+    elements.into_iter().map(|element| element as u64).collect()
+});
+impl_from_java_for_primitive_array!(char, jchar, "[C", get_char_array_region, |env, elements| {
+    elements
+        .into_iter()
+        .map(|element| <char as TryFromJava<jchar>>::try_from_java(env, element))
+        .collect::<Result<_, _>>()?
+});
+
+/// The JNI signature of an element type for [`FromJava`].
+struct FromJavaElement<'env, 'sub_env, T>(PhantomData<(&'env (), &'sub_env (), T)>);
+
+impl<'env, 'sub_env, T> ElementSignature for FromJavaElement<'env, 'sub_env, T>
+where
+    T: FromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+}
+
+/// The JNI signature of an element type for [`TryFromJava`].
+struct TryFromJavaElement<'env, 'sub_env, T>(PhantomData<(&'env (), &'sub_env (), T)>);
+
+impl<'env, 'sub_env, T> ElementSignature for TryFromJavaElement<'env, 'sub_env, T>
+where
+    T: TryFromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+}
+
+/// Converts all elements of a Java object array, failing if it isn't the expected length.
+fn try_object_array_from_java<'env, 'sub_env, T>(
+    env: &JnixEnv<'env>,
+    source: JObject<'sub_env>,
+    jni_signature: &'static str,
+    expected_length: Option<usize>,
+    mut convert_element: impl FnMut(JObject<'sub_env>) -> Result<T, JnixError>,
+) -> Result<Vec<T>, JnixError>
+where
+    'env: 'sub_env,
+{
+    let length = array_length(
+        env,
+        source,
+        "[Ljava/lang/Object;",
+        jni_signature,
+        expected_length,
+    )?;
+    let mut elements = Vec::with_capacity(length);
+
+    for index in 0..length {
+        let object = env
+            .get_object_array_element(source.into_inner(), index as jsize)
+            .for_class(env, jni_signature)?;
+
+        let element = convert_element(object);
+
+        let _ = env.delete_local_ref(object);
+
+        elements.push(element?);
+    }
+
+    Ok(elements)
+}
+
+impl<'env, 'sub_env, T, const N: usize> FromJava<'env, JObject<'sub_env>> for [T; N]
+where
+    'env: 'sub_env,
+    T: FromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str =
+        ArraySignature::<FromJavaElement<'env, 'sub_env, T>>::JNI_SIGNATURE;
+
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        let jni_signature = <Self as FromJava<'env, JObject<'sub_env>>>::JNI_SIGNATURE;
+
+        try_object_array_from_java(env, source, jni_signature, Some(N), |element| {
+            Ok(T::from_java(env, element))
+        })
+        .and_then(|elements| into_array(elements, jni_signature))
+        .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'env, 'sub_env, T, const N: usize> TryFromJava<'env, JObject<'sub_env>> for [T; N]
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str =
+        ArraySignature::<TryFromJavaElement<'env, 'sub_env, T>>::JNI_SIGNATURE;
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        let jni_signature = <Self as TryFromJava<'env, JObject<'sub_env>>>::JNI_SIGNATURE;
+        let elements =
+            try_object_array_from_java(env, source, jni_signature, Some(N), |element| {
+                T::try_from_java(env, element)
+            })?;
+
+        into_array(elements, jni_signature)
+    }
+}
+
+macro_rules! impl_from_java_for_object_array {
+    ( $( $rust_type:ty => |$elements:ident| $conversion:expr ),* $(,)* ) => {
+        $(
+            impl<'env, 'sub_env, T> FromJava<'env, JObject<'sub_env>> for JavaArray<$rust_type>
+            where
+                'env: 'sub_env,
+                T: FromJava<'env, JObject<'sub_env>>,
+            {
+                const JNI_SIGNATURE: &'static str =
+                    ArraySignature::<FromJavaElement<'env, 'sub_env, T>>::JNI_SIGNATURE;
+
+                fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                    let jni_signature = <Self as FromJava<'env, JObject<'sub_env>>>::JNI_SIGNATURE;
+
+                    try_object_array_from_java(env, source, jni_signature, None, |element| {
+                        Ok(T::from_java(env, element))
+                    })
+                    .map(|$elements| JavaArray($conversion))
+                    .unwrap_or_else(|error| panic!("{}", error))
+                }
+            }
+
+            impl<'env, 'sub_env, T> TryFromJava<'env, JObject<'sub_env>> for JavaArray<$rust_type>
+            where
+                'env: 'sub_env,
+                T: TryFromJava<'env, JObject<'sub_env>>,
+            {
+                const JNI_SIGNATURE: &'static str =
+                    ArraySignature::<TryFromJavaElement<'env, 'sub_env, T>>::JNI_SIGNATURE;
+
+                fn try_from_java(
+                    env: &JnixEnv<'env>,
+                    source: JObject<'sub_env>,
+                ) -> Result<Self, JnixError> {
+                    let jni_signature =
+                        <Self as TryFromJava<'env, JObject<'sub_env>>>::JNI_SIGNATURE;
+
+                    try_object_array_from_java(env, source, jni_signature, None, |element| {
+                        T::try_from_java(env, element)
+                    })
+                    .map(|$elements| JavaArray($conversion))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_java_for_object_array! {
+    Vec<T> => |elements| elements,
+    Box<[T]> => |elements| elements.into_boxed_slice(),
+}
+
+impl<'env, 'sub_env, T, const N: usize> FromJava<'env, JObject<'sub_env>> for JavaArray<[T; N]>
+where
+    'env: 'sub_env,
+    [T; N]: FromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str =
+        <[T; N] as FromJava<'env, JObject<'sub_env>>>::JNI_SIGNATURE;
+
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        JavaArray(<[T; N]>::from_java(env, source))
+    }
+}
+
+impl<'env, 'sub_env, T, const N: usize> TryFromJava<'env, JObject<'sub_env>> for JavaArray<[T; N]>
+where
+    'env: 'sub_env,
+    [T; N]: TryFromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str =
+        <[T; N] as TryFromJava<'env, JObject<'sub_env>>>::JNI_SIGNATURE;
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        <[T; N]>::try_from_java(env, source).map(JavaArray)
+    }
+}
@@ -1,13 +1,35 @@
+mod array;
 mod net;
 
-use crate::{FromJava, JnixEnv};
+use crate::{
+    boxing::{boxed_jni_signature, unbox},
+    error::JniResultExt,
+    CharString, Checked, CodePoint, FromJava, JavaList, JniArgument, JnixEnv, JnixError,
+    LoneSurrogates, StrictString, TryFromJava, Widened,
+};
 use jni::{
-    objects::{AutoLocal, JObject, JString, JValue},
+    objects::{AutoLocal, JClass, JObject, JString, JValue},
     signature::{JavaType, Primitive},
-    sys::{jboolean, jint, jlong, jshort, JNI_FALSE},
+    sys::{jboolean, jbyte, jchar, jdouble, jfloat, jint, jlong, jshort, JNI_FALSE},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    fmt::Display,
 };
-use std::collections::{HashMap, HashSet};
-use std::iter::FromIterator;
+
+fn object_from_jvalue<'sub_env>(
+    source: JValue<'sub_env>,
+    jni_signature: &'static str,
+) -> Result<JObject<'sub_env>, JnixError> {
+    match source {
+        JValue::Object(object) => Ok(object),
+        _ => Err(JnixError::invalid_value(format!(
+            "Can't convert non-object Java type. Expected type signature {}",
+            jni_signature
+        ))),
+    }
+}
 
 impl<'env, 'sub_env, T> FromJava<'env, JValue<'sub_env>> for T
 where
@@ -17,13 +39,22 @@ where
     const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
 
     fn from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Self {
-        match source {
-            JValue::Object(object) => T::from_java(env, object),
-            _ => panic!(
-                "Can't convert non-object Java type. Expected type signature {}",
-                Self::JNI_SIGNATURE
-            ),
-        }
+        let object = object_from_jvalue(source, Self::JNI_SIGNATURE)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        T::from_java(env, object)
+    }
+}
+
+impl<'env, 'sub_env, T> TryFromJava<'env, JValue<'sub_env>> for T
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Result<Self, JnixError> {
+        T::try_from_java(env, object_from_jvalue(source, Self::JNI_SIGNATURE)?)
     }
 }
 
@@ -40,116 +71,341 @@ where
     }
 }
 
-impl<'env> FromJava<'env, jboolean> for bool {
-    const JNI_SIGNATURE: &'static str = "Z";
+impl<'env, 'sub_env, 'borrow, T> TryFromJava<'env, AutoLocal<'sub_env, 'borrow>> for T
+where
+    'env: 'sub_env,
+    'sub_env: 'borrow,
+    T: for<'inner_borrow> TryFromJava<'env, JObject<'inner_borrow>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
 
-    fn from_java(_: &JnixEnv<'env>, source: jboolean) -> Self {
-        source != JNI_FALSE
+    fn try_from_java(
+        env: &JnixEnv<'env>,
+        source: AutoLocal<'sub_env, 'borrow>,
+    ) -> Result<Self, JnixError> {
+        T::try_from_java(env, source.as_obj())
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JValue<'sub_env>> for bool
+macro_rules! impl_from_java_for_primitive {
+    (
+        @fallible
+        $rust_type:ty,
+        $java_type:ty,
+        $signature:expr,
+        $variant:ident,
+        $description:expr,
+        |$source:ident| $conversion:expr
+    ) => {
+        impl<'env> TryFromJava<'env, $java_type> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn try_from_java(_: &JnixEnv<'env>, $source: $java_type) -> Result<Self, JnixError> {
+                $conversion
+            }
+        }
+
+        impl<'env> FromJava<'env, $java_type> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn from_java(env: &JnixEnv<'env>, source: $java_type) -> Self {
+                <$rust_type as TryFromJava<'env, $java_type>>::try_from_java(env, source)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+
+        impl<'env> JniArgument<'env> for $rust_type {
+            type JniType = $java_type;
+        }
+
+        impl<'env, 'sub_env> TryFromJava<'env, JValue<'sub_env>> for $rust_type
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn try_from_java(
+                env: &JnixEnv<'env>,
+                source: JValue<'sub_env>,
+            ) -> Result<Self, JnixError> {
+                match source {
+                    JValue::$variant(value) => {
+                        <$rust_type as TryFromJava<'env, $java_type>>::try_from_java(env, value)
+                    }
+                    _ => Err(JnixError::invalid_value(concat!(
+                        "Can't convert Java type, expected ",
+                        $description,
+                        " primitive"
+                    ))),
+                }
+            }
+        }
+
+        impl<'env, 'sub_env> FromJava<'env, JValue<'sub_env>> for $rust_type
+        where
+            'env: 'sub_env,
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            fn from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Self {
+                <$rust_type as TryFromJava<'env, JValue<'sub_env>>>::try_from_java(env, source)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+    };
+
+    (
+        $rust_type:ty,
+        $java_type:ty,
+        $signature:expr,
+        $variant:ident,
+        $description:expr,
+        |$source:ident| $conversion:expr
+    ) => {
+        impl_from_java_for_primitive!(
+            @fallible $rust_type,
+            $java_type,
+            $signature,
+            $variant,
+            $description,
+            |$source| Ok($conversion)
+        );
+    };
+}
+
+/// Converts a signed Java integer into an unsigned integer, failing if it's negative or too large.
+fn checked_into_unsigned<S, U>(value: S, rust_type: &'static str) -> Result<U, JnixError>
+where
+    S: Copy + Display,
+    U: TryFrom<S>,
+{
+    U::try_from(value).map_err(|_| {
+        JnixError::invalid_value(format!(
+            "Java value {} is out of range for a Rust {}",
+            value, rust_type
+        ))
+    })
+}
+
+impl_from_java_for_primitive!(bool, jboolean, "Z", Bool, "a boolean", |source| {
+    source != JNI_FALSE
+});
+impl_from_java_for_primitive!(i64, jlong, "J", Long, "a long", |source| source);
+impl_from_java_for_primitive!(i32, jint, "I", Int, "an integer", |source| source);
+impl_from_java_for_primitive!(i16, jshort, "S", Short, "a short", |source| source);
+impl_from_java_for_primitive!(f64, jdouble, "D", Double, "a double", |source| source);
+impl_from_java_for_primitive!(i8, jbyte, "B", Byte, "a byte", |source| source);
+impl_from_java_for_primitive!(f32, jfloat, "F", Float, "a float", |source| source);
+
+impl_from_java_for_primitive!(u8, jbyte, "B", Byte, "a byte", |source| source as u8);
+impl_from_java_for_primitive!(u16, jchar, "C", Char, "a char", |source| source);
+impl_from_java_for_primitive!(u32, jint, "I", Int, "an integer", |source| source as u32);
+impl_from_java_for_primitive!(u64, jlong, "J", Long, "a long", |source| source as u64);
+
+impl_from_java_for_primitive!(@fallible Widened<u8>, jshort, "S", Short, "a short", |source| {
+    checked_into_unsigned(source, "u8").map(Widened)
+});
+impl_from_java_for_primitive!(@fallible Widened<u16>, jint, "I", Int, "an integer", |source| {
+    checked_into_unsigned(source, "u16").map(Widened)
+});
+impl_from_java_for_primitive!(@fallible Widened<u32>, jlong, "J", Long, "a long", |source| {
+    checked_into_unsigned(source, "u32").map(Widened)
+});
+
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for Widened<u64>
 where
     'env: 'sub_env,
 {
-    const JNI_SIGNATURE: &'static str = "Z";
+    const JNI_SIGNATURE: &'static str = "Ljava/math/BigInteger;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        let int_type = JavaType::Primitive(Primitive::Int);
+
+        let signum = env
+            .call_cached_method(
+                source,
+                "java/math/BigInteger",
+                "signum",
+                "()I",
+                int_type.clone(),
+                &[],
+            )?
+            .i()
+            .for_member(env, "java/math/BigInteger", "signum", "()I")?;
+        let bit_length = env
+            .call_cached_method(
+                source,
+                "java/math/BigInteger",
+                "bitLength",
+                "()I",
+                int_type,
+                &[],
+            )?
+            .i()
+            .for_member(env, "java/math/BigInteger", "bitLength", "()I")?;
 
-    fn from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Self {
-        match source {
-            JValue::Bool(boolean) => bool::from_java(env, boolean),
-            _ => panic!("Can't convert Java type, expected a boolean primitive"),
+        if signum < 0 || bit_length > 64 {
+            return Err(JnixError::invalid_value(
+                "Java BigInteger value is out of range for a Rust u64",
+            )
+            .with_class("java/math/BigInteger"));
         }
+
+        let bits = env
+            .call_cached_method(
+                source,
+                "java/math/BigInteger",
+                "longValue",
+                "()J",
+                JavaType::Primitive(Primitive::Long),
+                &[],
+            )?
+            .j()
+            .for_member(env, "java/math/BigInteger", "longValue", "()J")?;
+
+        Ok(Widened(bits as u64))
     }
 }
 
-impl<'env> FromJava<'env, jlong> for i64 {
-    const JNI_SIGNATURE: &'static str = "J";
+impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for Widened<u64>
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/math/BigInteger;";
 
-    fn from_java(_: &JnixEnv<'env>, source: jlong) -> Self {
-        source as i64
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        Widened::<u64>::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JValue<'sub_env>> for i64
+impl_from_java_for_primitive!(@fallible char, jchar, "C", Char, "a char", |source| {
+    char::from_u32(source as u32).ok_or_else(|| {
+        JnixError::invalid_value(format!(
+            "Java char U+{:04X} is a surrogate code unit, which isn't a valid Rust char",
+            source
+        ))
+    })
+});
+impl_from_java_for_primitive!(@fallible CodePoint, jint, "I", Int, "an integer", |source| {
+    char::from_u32(source as u32)
+        .map(CodePoint)
+        .ok_or_else(|| {
+            JnixError::invalid_value(format!(
+                "Java int {} is not a valid Unicode code point for a Rust char",
+                source
+            ))
+        })
+});
+
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for CharString
 where
     'env: 'sub_env,
 {
-    const JNI_SIGNATURE: &'static str = "J";
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    fn from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Self {
-        match source {
-            JValue::Long(long) => i64::from_java(env, long),
-            _ => panic!("Can't convert Java type, expected a long primitive"),
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        let string = env.get_utf16_string(JString::from(source), LoneSurrogates::Fail)?;
+        let mut characters = string.chars();
+
+        match (characters.next(), characters.next()) {
+            (Some(character), None) => Ok(CharString(character)),
+            _ => Err(JnixError::invalid_value(format!(
+                "Java string {:?} doesn't contain exactly one character",
+                string
+            ))
+            .with_class("java/lang/String")),
         }
     }
 }
 
-impl<'env> FromJava<'env, jint> for i32 {
-    const JNI_SIGNATURE: &'static str = "I";
+impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for CharString
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    fn from_java(_: &JnixEnv<'env>, source: jint) -> Self {
-        source as i32
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        CharString::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JValue<'sub_env>> for i32
+impl_from_java_for_primitive!(@fallible Checked<u8>, jbyte, "B", Byte, "a byte", |source| {
+    checked_into_unsigned(source, "u8").map(Checked)
+});
+impl_from_java_for_primitive!(Checked<u16>, jchar, "C", Char, "a char", |source| {
+    Checked(source)
+});
+impl_from_java_for_primitive!(@fallible Checked<u32>, jint, "I", Int, "an integer", |source| {
+    checked_into_unsigned(source, "u32").map(Checked)
+});
+impl_from_java_for_primitive!(@fallible Checked<u64>, jlong, "J", Long, "a long", |source| {
+    checked_into_unsigned(source, "u64").map(Checked)
+});
+
+impl<'env, 'sub_env> TryFromJava<'env, JString<'sub_env>> for String
 where
     'env: 'sub_env,
 {
-    const JNI_SIGNATURE: &'static str = "I";
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    fn from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Self {
-        match source {
-            JValue::Int(integer) => i32::from_java(env, integer),
-            _ => panic!("Can't convert Java type, expected an integer primitive"),
-        }
+    fn try_from_java(env: &JnixEnv<'env>, source: JString<'sub_env>) -> Result<Self, JnixError> {
+        env.get_utf16_string(source, LoneSurrogates::Replace)
     }
 }
 
-impl<'env> FromJava<'env, jshort> for i16 {
-    const JNI_SIGNATURE: &'static str = "S";
+impl<'env, 'sub_env> FromJava<'env, JString<'sub_env>> for String
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    fn from_java(_: &JnixEnv<'env>, source: jshort) -> Self {
-        source as i16
+    fn from_java(env: &JnixEnv<'env>, source: JString<'sub_env>) -> Self {
+        String::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JValue<'sub_env>> for i16
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for String
 where
     'env: 'sub_env,
 {
-    const JNI_SIGNATURE: &'static str = "S";
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    fn from_java(env: &JnixEnv<'env>, source: JValue<'sub_env>) -> Self {
-        match source {
-            JValue::Short(short) => i16::from_java(env, short),
-            _ => panic!("Can't convert Java type, expected a short primitive"),
-        }
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        String::try_from_java(env, JString::from(source))
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JString<'sub_env>> for String
+impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for String
 where
     'env: 'sub_env,
 {
     const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    fn from_java(env: &JnixEnv<'env>, source: JString<'sub_env>) -> Self {
-        String::from(
-            env.get_string(source)
-                .expect("Failed to convert from Java String"),
-        )
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        String::from_java(env, JString::from(source))
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for String
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for StrictString
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        env.get_utf16_string(JString::from(source), LoneSurrogates::Fail)
+            .map(StrictString)
+    }
+}
+
+impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for StrictString
 where
     'env: 'sub_env,
 {
     const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
     fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        String::from_java(env, JString::from(source))
+        StrictString::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
@@ -169,6 +425,22 @@ where
     }
 }
 
+impl<'env, 'sub_env, T> TryFromJava<'env, JObject<'sub_env>> for Option<T>
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        if source.is_null() {
+            Ok(None)
+        } else {
+            T::try_from_java(env, source).map(Some)
+        }
+    }
+}
+
 impl<'env, 'sub_env, T> FromJava<'env, JString<'sub_env>> for Option<T>
 where
     'env: 'sub_env,
@@ -185,30 +457,253 @@ where
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for Option<i32> {
-    const JNI_SIGNATURE: &'static str = "Ljava/lang/Integer;";
+impl<'env, 'sub_env, T> TryFromJava<'env, JString<'sub_env>> for Option<T>
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JString<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
 
-    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+    fn try_from_java(env: &JnixEnv<'env>, source: JString<'sub_env>) -> Result<Self, JnixError> {
         if source.is_null() {
-            None
+            Ok(None)
         } else {
-            let class = env.get_class("java/lang/Integer");
-            let method_id = env
-                .get_method_id(&class, "intValue", "()I")
-                .expect("Failed to get method ID for Integer.intValue()");
-            let return_type = JavaType::Primitive(Primitive::Int);
-
-            let int_value = env
-                .call_method_unchecked(source, method_id, return_type, &[])
-                .expect("Failed to call Integer.intValue()")
-                .i()
-                .expect("Call to Integer.intValue() did not return an int primitive");
-
-            Some(int_value)
+            T::try_from_java(env, source).map(Some)
+        }
+    }
+}
+
+macro_rules! impl_from_java_for_boxed_primitive {
+    ( $( $rust_type:ty ),* $(,)* ) => {
+        $(
+            impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for Option<$rust_type>
+            where
+                'env: 'sub_env,
+            {
+                const JNI_SIGNATURE: &'static str = boxed_jni_signature(
+                    <$rust_type as TryFromJava<'env, JValue<'sub_env>>>::JNI_SIGNATURE,
+                );
+
+                fn try_from_java(
+                    env: &JnixEnv<'env>,
+                    source: JObject<'sub_env>,
+                ) -> Result<Self, JnixError> {
+                    if source.is_null() {
+                        Ok(None)
+                    } else {
+                        let primitive_signature =
+                            <$rust_type as TryFromJava<'env, JValue<'sub_env>>>::JNI_SIGNATURE;
+                        let value = unbox(env, source, primitive_signature)?;
+
+                        <$rust_type>::try_from_java(env, value).map(Some)
+                    }
+                }
+            }
+
+            impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for Option<$rust_type>
+            where
+                'env: 'sub_env,
+            {
+                const JNI_SIGNATURE: &'static str = boxed_jni_signature(
+                    <$rust_type as FromJava<'env, JValue<'sub_env>>>::JNI_SIGNATURE,
+                );
+
+                fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                    <Option<$rust_type>>::try_from_java(env, source)
+                        .unwrap_or_else(|error| panic!("{}", error))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_java_for_boxed_primitive!(bool, char, i8, i16, i32, i64, f32, f64, u8, u16, u32, u64);
+
+/// Converts an element of a Java collection, unboxing it if the Rust type is a primitive.
+pub(crate) fn element_from_java<'env, 'sub_env, T>(
+    env: &JnixEnv<'env>,
+    element: JObject<'sub_env>,
+) -> Result<T, JnixError>
+where
+    'env: 'sub_env,
+    T: FromJava<'env, JValue<'sub_env>>,
+{
+    Ok(T::from_java(env, unbox(env, element, T::JNI_SIGNATURE)?))
+}
+
+/// Converts an element of a Java collection, unboxing it if the Rust type is a primitive.
+pub(crate) fn try_element_from_java<'env, 'sub_env, T>(
+    env: &JnixEnv<'env>,
+    element: JObject<'sub_env>,
+) -> Result<T, JnixError>
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JValue<'sub_env>>,
+{
+    T::try_from_java(env, unbox(env, element, T::JNI_SIGNATURE)?)
+}
+
+/// Fails if the source object isn't an instance of a class or interface.
+///
+/// Methods are called on collections through their interfaces, and calling an interface method on
+/// an object that doesn't implement it is undefined behavior.
+fn check_instance_of(
+    env: &JnixEnv,
+    source: JObject,
+    class_name: &'static str,
+) -> Result<(), JnixError> {
+    if source.is_null() {
+        return Err(
+            JnixError::invalid_value("Java collection reference is null").with_class(class_name),
+        );
+    }
+
+    let class = env.try_get_class(class_name)?;
+    let is_instance = env
+        .is_instance_of(source, JClass::from(class.as_obj()))
+        .for_class(env, class_name)?;
+
+    if is_instance {
+        Ok(())
+    } else {
+        Err(
+            JnixError::invalid_value(format!("Java object is not an instance of {}", class_name))
+                .with_class(class_name),
+        )
+    }
+}
+
+/// Returns the number of elements in a non-null `java.lang.Iterable`, or zero if it isn't a
+/// `java.util.Collection`.
+///
+/// The result is only used as the initial capacity of the Rust collection.
+fn initial_capacity(env: &JnixEnv, source: JObject) -> Result<usize, JnixError> {
+    let collection_class = env.try_get_class("java/util/Collection")?;
+    let is_collection = env
+        .is_instance_of(source, JClass::from(collection_class.as_obj()))
+        .for_class(env, "java/util/Collection")?;
+
+    if !is_collection {
+        return Ok(0);
+    }
+
+    let size = env
+        .call_cached_method(
+            source,
+            "java/util/Collection",
+            "size",
+            "()I",
+            JavaType::Primitive(Primitive::Int),
+            &[],
+        )?
+        .i()
+        .for_member(env, "java/util/Collection", "size", "()I")?;
+
+    Ok(size.max(0) as usize)
+}
+
+/// Visits each remaining element of a `java.util.Iterator`.
+fn try_for_each_remaining<'env, 'sub_env>(
+    env: &JnixEnv<'env>,
+    iterator: JObject<'sub_env>,
+    mut visit_element: impl FnMut(JObject<'sub_env>) -> Result<(), JnixError>,
+) -> Result<(), JnixError>
+where
+    'env: 'sub_env,
+{
+    loop {
+        let has_next = env
+            .call_cached_method(
+                iterator,
+                "java/util/Iterator",
+                "hasNext",
+                "()Z",
+                JavaType::Primitive(Primitive::Boolean),
+                &[],
+            )?
+            .z()
+            .for_member(env, "java/util/Iterator", "hasNext", "()Z")?;
+
+        if !has_next {
+            return Ok(());
         }
+
+        let element = env
+            .call_cached_method(
+                iterator,
+                "java/util/Iterator",
+                "next",
+                "()Ljava/lang/Object;",
+                JavaType::Object("java/lang/Object".to_owned()),
+                &[],
+            )?
+            .l()
+            .for_member(env, "java/util/Iterator", "next", "()Ljava/lang/Object;")?;
+
+        let visit_result = visit_element(element);
+
+        let _ = env.delete_local_ref(element);
+
+        visit_result?;
     }
 }
 
+/// Visits each element of a `java.lang.Iterable` using its iterator.
+///
+/// The source object must be checked to be an `Iterable` first. The local reference to each element
+/// is deleted after it is visited.
+fn try_for_each_element<'env, 'sub_env>(
+    env: &JnixEnv<'env>,
+    source: JObject<'sub_env>,
+    visit_element: impl FnMut(JObject<'sub_env>) -> Result<(), JnixError>,
+) -> Result<(), JnixError>
+where
+    'env: 'sub_env,
+{
+    let iterator = env
+        .call_cached_method(
+            source,
+            "java/lang/Iterable",
+            "iterator",
+            "()Ljava/util/Iterator;",
+            JavaType::Object("java/util/Iterator".to_owned()),
+            &[],
+        )?
+        .l()
+        .for_member(
+            env,
+            "java/lang/Iterable",
+            "iterator",
+            "()Ljava/util/Iterator;",
+        )?;
+
+    let result = try_for_each_remaining(env, iterator, visit_element);
+
+    let _ = env.delete_local_ref(iterator);
+
+    result
+}
+
+fn try_vec_from_java<'env, 'sub_env, T>(
+    env: &JnixEnv<'env>,
+    source: JObject<'sub_env>,
+    mut convert_element: impl FnMut(JObject<'sub_env>) -> Result<T, JnixError>,
+) -> Result<Vec<T>, JnixError>
+where
+    'env: 'sub_env,
+{
+    check_instance_of(env, source, "java/lang/Iterable")?;
+
+    let mut target = Vec::with_capacity(initial_capacity(env, source)?);
+
+    try_for_each_element(env, source, |element| {
+        target.push(convert_element(element)?);
+        Ok(())
+    })?;
+
+    Ok(target)
+}
+
 impl<'env, 'sub_env, T> FromJava<'env, JObject<'sub_env>> for Vec<T>
 where
     'env: 'sub_env,
@@ -217,121 +712,213 @@ where
     const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
     fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        let class = env.get_class("java/util/ArrayList");
-        let size_method_id = env
-            .get_method_id(&class, "size", "()I")
-            .expect("Failed to get method ID for ArrayList.size()");
-        let size_return_type = JavaType::Primitive(Primitive::Int);
-
-        let item_count = env
-            .call_method_unchecked(source, size_method_id, size_return_type, &[])
-            .expect("Failed to call ArrayList.size()")
-            .i()
-            .expect("Call to ArrayList.size() did not return an int primitive");
-
-        let mut target = Vec::with_capacity(item_count as usize);
-
-        let get_method_id = env
-            .get_method_id(&class, "get", "(I)Ljava/lang/Object;")
-            .expect("Failed to get method ID for ArrayList.get()");
-        let get_return_type = JavaType::Object("java/lang/Object".to_owned());
-
-        for index in 0..item_count {
-            let object = env
-                .call_method_unchecked(
-                    source,
-                    get_method_id,
-                    get_return_type.clone(),
-                    &[JValue::Int(index)],
-                )
-                .expect("Failed to call ArrayList.get()")
-                .l()
-                .expect("Call to ArrayList.get() did not return an object");
-            let item = T::from_java(env, object);
-
-            target.push(item);
-        }
+        try_vec_from_java(env, source, |element| Ok(T::from_java(env, element)))
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
 
-        target
+impl<'env, 'sub_env, T> TryFromJava<'env, JObject<'sub_env>> for Vec<T>
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JObject<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        try_vec_from_java(env, source, |element| T::try_from_java(env, element))
     }
 }
 
-impl<'env, 'sub_env, T: Eq + std::hash::Hash> FromJava<'env, JObject<'sub_env>> for HashSet<T>
+impl<'env, 'sub_env, T> FromJava<'env, JObject<'sub_env>> for JavaList<Vec<T>>
 where
     'env: 'sub_env,
-    T: FromJava<'env, JObject<'sub_env>>,
+    T: FromJava<'env, JValue<'sub_env>>,
 {
-    const JNI_SIGNATURE: &'static str = "Ljava/util/HashSet;";
+    const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
     fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        let class = env.get_class("java/util/ArrayList");
+        try_vec_from_java(env, source, |element| element_from_java(env, element))
+            .map(JavaList)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
 
-        let list_object = env
-            .new_object(&class, "(Ljava/util/Collection;)V", &[JValue::from(source)])
-            .expect("Failed to create ArrayList object from HashSet");
+impl<'env, 'sub_env, T> TryFromJava<'env, JObject<'sub_env>> for JavaList<Vec<T>>
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JValue<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
-        let vector = Vec::from_java(&env, list_object);
-        HashSet::from_iter(vector)
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        try_vec_from_java(env, source, |element| try_element_from_java(env, element)).map(JavaList)
     }
 }
 
-impl<'env, 'sub_env, K, V> FromJava<'env, JObject<'sub_env>> for HashMap<K, V>
+fn try_hash_set_from_java<'env, 'sub_env, T>(
+    env: &JnixEnv<'env>,
+    source: JObject<'sub_env>,
+    mut convert_element: impl FnMut(JObject<'sub_env>) -> Result<T, JnixError>,
+) -> Result<HashSet<T>, JnixError>
 where
     'env: 'sub_env,
-    K: FromJava<'env, JObject<'sub_env>> + Eq + std::hash::Hash,
-    V: FromJava<'env, JObject<'sub_env>>,
+    T: Eq + std::hash::Hash,
 {
-    const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+    check_instance_of(env, source, "java/lang/Iterable")?;
+
+    let mut set = HashSet::with_capacity(initial_capacity(env, source)?);
+
+    try_for_each_element(env, source, |element| {
+        set.insert(convert_element(element)?);
+        Ok(())
+    })?;
+
+    Ok(set)
+}
+
+impl<'env, 'sub_env, T: Eq + std::hash::Hash> FromJava<'env, JObject<'sub_env>> for HashSet<T>
+where
+    'env: 'sub_env,
+    T: FromJava<'env, JValue<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/HashSet;";
 
     fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        let entry_set = env
-            .call_method(source, "entrySet", "()Ljava/util/Set;", &[])
-            .expect("Failed to call Map.entrySet()")
-            .l()
-            .expect("Entry set should be an object");
+        try_hash_set_from_java(env, source, |element| element_from_java(env, element))
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'env, 'sub_env, T: Eq + std::hash::Hash> TryFromJava<'env, JObject<'sub_env>> for HashSet<T>
+where
+    'env: 'sub_env,
+    T: TryFromJava<'env, JValue<'sub_env>>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/HashSet;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        try_hash_set_from_java(env, source, |element| try_element_from_java(env, element))
+    }
+}
 
-        let iterator = env
-            .call_method(entry_set, "iterator", "()Ljava/util/Iterator;", &[])
-            .expect("Failed to get iterator")
+/// Converts the entries of a `java.util.Map` into a Rust map, in the iteration order of the Java
+/// map.
+pub(crate) fn try_map_from_java<'env, 'sub_env, K, V, M>(
+    env: &JnixEnv<'env>,
+    source: JObject<'sub_env>,
+    mut convert_entry: impl FnMut(JObject<'sub_env>, JObject<'sub_env>) -> Result<(K, V), JnixError>,
+) -> Result<M, JnixError>
+where
+    'env: 'sub_env,
+    M: Default + Extend<(K, V)>,
+{
+    check_instance_of(env, source, "java/util/Map")?;
+
+    let object_type = JavaType::Object("java/lang/Object".to_owned());
+
+    let entry_set = env
+        .call_cached_method(
+            source,
+            "java/util/Map",
+            "entrySet",
+            "()Ljava/util/Set;",
+            JavaType::Object("java/util/Set".to_owned()),
+            &[],
+        )?
+        .l()
+        .for_member(env, "java/util/Map", "entrySet", "()Ljava/util/Set;")?;
+
+    let mut map = M::default();
+
+    let result = try_for_each_element(env, entry_set, |entry| {
+        let key = env
+            .call_cached_method(
+                entry,
+                "java/util/Map$Entry",
+                "getKey",
+                "()Ljava/lang/Object;",
+                object_type.clone(),
+                &[],
+            )?
             .l()
-            .expect("Iterator should be an object");
+            .for_member(env, "java/util/Map$Entry", "getKey", "()Ljava/lang/Object;")?;
+
+        let value = env
+            .call_cached_method(
+                entry,
+                "java/util/Map$Entry",
+                "getValue",
+                "()Ljava/lang/Object;",
+                object_type.clone(),
+                &[],
+            )?
+            .l()
+            .for_member(
+                env,
+                "java/util/Map$Entry",
+                "getValue",
+                "()Ljava/lang/Object;",
+            )?;
 
-        let mut map = HashMap::new();
+        let entry_result = convert_entry(key, value);
 
-        loop {
-            let has_next = env
-                .call_method(iterator, "hasNext", "()Z", &[])
-                .expect("Failed to call hasNext()")
-                .z()
-                .expect("hasNext should return boolean");
+        let _ = env.delete_local_ref(value);
+        let _ = env.delete_local_ref(key);
 
-            if !has_next {
-                break;
-            }
+        map.extend(Some(entry_result?));
+
+        Ok(())
+    });
 
-            let entry = env
-                .call_method(iterator, "next", "()Ljava/lang/Object;", &[])
-                .expect("Failed to call next()")
-                .l()
-                .expect("next() should return Map.Entry");
+    let _ = env.delete_local_ref(entry_set);
 
-            let key = env
-                .call_method(entry, "getKey", "()Ljava/lang/Object;", &[])
-                .expect("Failed to call getKey()")
-                .l()
-                .expect("Key should be object");
+    result.map(|()| map)
+}
 
-            let value = env
-                .call_method(entry, "getValue", "()Ljava/lang/Object;", &[])
-                .expect("Failed to call getValue()")
-                .l()
-                .expect("Value should be object");
+macro_rules! impl_from_java_for_map {
+    ( $( $rust_type:ident $( + $key_bound:path )* ),* $(,)* ) => {
+        $(
+            impl<'env, 'sub_env, K, V> FromJava<'env, JObject<'sub_env>> for $rust_type<K, V>
+            where
+                'env: 'sub_env,
+                K: FromJava<'env, JValue<'sub_env>> $( + $key_bound )*,
+                V: FromJava<'env, JValue<'sub_env>>,
+            {
+                const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+                fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+                    try_map_from_java(env, source, |key, value| {
+                        Ok((element_from_java(env, key)?, element_from_java(env, value)?))
+                    })
+                    .unwrap_or_else(|error| panic!("{}", error))
+                }
+            }
 
-            let rust_key = K::from_java(env, key);
-            let rust_value = V::from_java(env, value);
+            impl<'env, 'sub_env, K, V> TryFromJava<'env, JObject<'sub_env>> for $rust_type<K, V>
+            where
+                'env: 'sub_env,
+                K: TryFromJava<'env, JValue<'sub_env>> $( + $key_bound )*,
+                V: TryFromJava<'env, JValue<'sub_env>>,
+            {
+                const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+                fn try_from_java(
+                    env: &JnixEnv<'env>,
+                    source: JObject<'sub_env>,
+                ) -> Result<Self, JnixError> {
+                    try_map_from_java(env, source, |key, value| {
+                        Ok((
+                            try_element_from_java(env, key)?,
+                            try_element_from_java(env, value)?,
+                        ))
+                    })
+                }
+            }
+        )*
+    };
+}
 
-            map.insert(rust_key, rust_value);
-        }
-        map
-    }
+impl_from_java_for_map! {
+    HashMap + Eq + std::hash::Hash,
+    BTreeMap + Ord,
 }
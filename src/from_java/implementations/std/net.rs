@@ -1,27 +1,36 @@
-use crate::{FromJava, JnixEnv};
+use crate::{error::JniResultExt, FromJava, JnixEnv, JnixError, TryFromJava};
 use jni::{
     objects::JObject,
     signature::{JavaType, Primitive},
 };
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-fn read_inet_address_octets<'env, 'o>(env: &JnixEnv<'env>, source: JObject<'o>) -> JObject<'env>
+fn read_inet_address_octets<'env, 'o>(
+    env: &JnixEnv<'env>,
+    source: JObject<'o>,
+) -> Result<JObject<'env>, JnixError>
 where
     'o: 'env,
 {
-    let class = env.get_class("java/net/InetAddress");
-    let method_id = env
-        .get_method_id(&class, "getAddress", "()[B")
-        .expect("Failed to get method ID for InetAddress.getAddress()");
     let return_type = JavaType::Array(Box::new(JavaType::Primitive(Primitive::Byte)));
 
-    env.call_method_unchecked(source, method_id, return_type, &[])
-        .expect("Failed to call InetAddress.getAddress()")
-        .l()
-        .expect("Call to InetAddress.getAddress() did not return an object")
+    env.call_cached_method(
+        source,
+        "java/net/InetAddress",
+        "getAddress",
+        "()[B",
+        return_type,
+        &[],
+    )?
+    .l()
+    .for_member(env, "java/net/InetAddress", "getAddress", "()[B")
 }
 
-fn address_from_octets<'env, 'o, A, B>(env: &JnixEnv<'env>, octets: JObject<'o>, mut buffer: B) -> A
+fn address_from_octets<'env, 'o, A, B>(
+    env: &JnixEnv<'env>,
+    octets: JObject<'o>,
+    mut buffer: B,
+) -> Result<A, JnixError>
 where
     'o: 'env,
     A: From<B>,
@@ -32,13 +41,26 @@ where
     let buffer_size = buffer_octets.len();
 
     env.get_byte_array_region(octets.into_inner(), 0, &mut signed_octets[..buffer_size])
-        .expect("Failed to read octets returned by InetAddress.getAddress()");
+        .for_member(env, "java/net/InetAddress", "getAddress", "()[B")?;
 
-    for index in 0..buffer_size {
-        buffer_octets[index] = signed_octets[index] as u8;
+    for (octet, signed_octet) in buffer_octets.iter_mut().zip(signed_octets.iter()) {
+        *octet = *signed_octet as u8;
     }
 
-    A::from(buffer)
+    Ok(A::from(buffer))
+}
+
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for Ipv4Addr
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/net/Inet4Address;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        let octets = read_inet_address_octets(env, source)?;
+
+        address_from_octets(env, octets, [0u8; 4])
+    }
 }
 
 impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for Ipv4Addr
@@ -48,9 +70,20 @@ where
     const JNI_SIGNATURE: &'static str = "Ljava/net/Inet4Address;";
 
     fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        let octets = read_inet_address_octets(env, source);
+        Ipv4Addr::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
+    }
+}
 
-        address_from_octets(env, octets, [0u8; 4])
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for Ipv6Addr
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/net/Inet6Address;";
+
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        let octets = read_inet_address_octets(env, source)?;
+
+        address_from_octets(env, octets, [0u8; 16])
     }
 }
 
@@ -61,31 +94,43 @@ where
     const JNI_SIGNATURE: &'static str = "Ljava/net/Inet6Address;";
 
     fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        let octets = read_inet_address_octets(env, source);
-
-        address_from_octets(env, octets, [0u8; 16])
+        Ipv6Addr::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for IpAddr
+impl<'env, 'sub_env> TryFromJava<'env, JObject<'sub_env>> for IpAddr
 where
     'env: 'sub_env,
 {
     const JNI_SIGNATURE: &'static str = "Ljava/net/InetAddress;";
 
-    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
-        let octets = read_inet_address_octets(env, source);
-        let octet_count = env
-            .get_array_length(octets.into_inner())
-            .expect("Failed to get length of byte array returned by InetAddress.getAddress()");
+    fn try_from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Result<Self, JnixError> {
+        let octets = read_inet_address_octets(env, source)?;
+        let octet_count = env.get_array_length(octets.into_inner()).for_member(
+            env,
+            "java/net/InetAddress",
+            "getAddress",
+            "()[B",
+        )?;
 
         match octet_count {
             4 => address_from_octets(env, octets, [0u8; 4]),
             16 => address_from_octets(env, octets, [0u8; 16]),
-            count => panic!(
+            count => Err(JnixError::invalid_value(format!(
                 "Invalid number of octets returned by InetAddress.getAddress(): {}",
                 count
-            ),
+            ))),
         }
     }
 }
+
+impl<'env, 'sub_env> FromJava<'env, JObject<'sub_env>> for IpAddr
+where
+    'env: 'sub_env,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/net/InetAddress;";
+
+    fn from_java(env: &JnixEnv<'env>, source: JObject<'sub_env>) -> Self {
+        IpAddr::try_from_java(env, source).unwrap_or_else(|error| panic!("{}", error))
+    }
+}
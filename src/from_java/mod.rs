@@ -1,10 +1,12 @@
 mod implementations;
 
-use crate::JnixEnv;
+use crate::{JnixEnv, JnixError};
 
 /// Conversion from a Java type into its Rust equivalent.
 ///
 /// A type that implements this trait has an equivalent Java representation.
+///
+/// The conversion panics if it fails. See [`TryFromJava`] for a fallible version.
 pub trait FromJava<'env, JavaType> {
     /// The [JNI signature] of the Java type.
     ///
@@ -14,3 +16,16 @@ pub trait FromJava<'env, JavaType> {
     /// Performs the conversion.
     fn from_java(env: &JnixEnv<'env>, source: JavaType) -> Self;
 }
+
+/// Fallible conversion from a Java type into its Rust equivalent.
+///
+/// A type that implements this trait has an equivalent Java representation.
+pub trait TryFromJava<'env, JavaType>: Sized {
+    /// The [JNI signature] of the Java type.
+    ///
+    /// [JNI signature]: https://docs.oracle.com/javase/7/docs/technotes/guides/jni/spec/types.html#wp16432
+    const JNI_SIGNATURE: &'static str;
+
+    /// Performs the conversion, returning an error if it fails.
+    fn try_from_java(env: &JnixEnv<'env>, source: JavaType) -> Result<Self, JnixError>;
+}
@@ -0,0 +1,45 @@
+use super::std::try_map_into_java;
+use crate::{IntoJava, JnixEnv, JnixError, TryIntoJava};
+use indexmap::IndexMap;
+use jni::objects::AutoLocal;
+
+impl<'borrow, 'env, K, V> IntoJava<'borrow, 'env> for IndexMap<K, V>
+where
+    'env: 'borrow,
+    K: IntoJava<'borrow, 'env>,
+    V: IntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        try_map_into_java(
+            env,
+            "java/util/LinkedHashMap",
+            self.into_iter(),
+            |key, value| Ok((key.into_java(env), value.into_java(env))),
+        )
+        .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'borrow, 'env, K, V> TryIntoJava<'borrow, 'env> for IndexMap<K, V>
+where
+    'env: 'borrow,
+    K: TryIntoJava<'borrow, 'env>,
+    V: TryIntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        try_map_into_java(
+            env,
+            "java/util/LinkedHashMap",
+            self.into_iter(),
+            |key, value| Ok((key.try_into_java(env)?, value.try_into_java(env)?)),
+        )
+    }
+}
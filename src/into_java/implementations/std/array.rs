@@ -0,0 +1,454 @@
+use crate::{
+    array_signature::{ArraySignature, ElementSignature},
+    error::JniResultExt,
+    IntoJava, JavaArray, JnixEnv, JnixError, TryIntoJava,
+};
+use jni::{
+    objects::{AutoLocal, JClass, JObject},
+    sys::{jboolean, jbyte, jchar, jdouble, jfloat, jint, jlong, jshort, jsize},
+};
+use std::{borrow::Cow, marker::PhantomData, slice};
+
+macro_rules! impl_into_java_for_primitive_array {
+    (
+        $element_type:ty,
+        $java_element_type:ty,
+        $signature:expr,
+        $new_array:ident,
+        $set_array_region:ident,
+        |$env:ident, $elements:ident| $conversion:expr
+    ) => {
+        impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for &'_ [$element_type] {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+                let java_elements: Cow<[$java_element_type]> = {
+                    let $env = env;
+                    let $elements = self;
+
+                    $conversion
+                };
+
+                let array = env
+                    .$new_array(java_elements.len() as jsize)
+                    .for_class(env, $signature)?;
+                let array_object = env.auto_local(JObject::from(array));
+
+                env.$set_array_region(array, 0, &java_elements)
+                    .for_class(env, $signature)?;
+
+                Ok(array_object)
+            }
+        }
+
+        impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for &'_ [$element_type] {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                self.try_into_java(env)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+
+        impl_into_java_for_primitive_array!(@owned Vec<$element_type>, $element_type, $signature);
+        impl_into_java_for_primitive_array!(@owned Box<[$element_type]>, $element_type, $signature);
+        impl_into_java_for_primitive_array!(@array $element_type, $signature);
+        impl_into_java_for_primitive_array!(@wrapped JavaArray<Vec<$element_type>>, $signature);
+        impl_into_java_for_primitive_array!(@wrapped JavaArray<Box<[$element_type]>>, $signature);
+    };
+
+    (@wrapped $rust_type:ty, $signature:expr) => {
+        impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+                self.0.try_into_java(env)
+            }
+        }
+
+        impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                self.0.into_java(env)
+            }
+        }
+    };
+
+    (@owned $rust_type:ty, $element_type:ty, $signature:expr) => {
+        impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+                (&self[..]).try_into_java(env)
+            }
+        }
+
+        impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                (&self[..]).into_java(env)
+            }
+        }
+    };
+
+    (@array $element_type:ty, $signature:expr) => {
+        impl<'borrow, 'env: 'borrow, const N: usize> TryIntoJava<'borrow, 'env>
+            for [$element_type; N]
+        {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+                (&self[..]).try_into_java(env)
+            }
+        }
+
+        impl<'borrow, 'env: 'borrow, const N: usize> IntoJava<'borrow, 'env> for [$element_type; N] {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = AutoLocal<'env, 'borrow>;
+
+            fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                (&self[..]).into_java(env)
+            }
+        }
+    };
+}
+
+/// Reinterprets a slice of unsigned integers as a slice of the signed Java integers of the same
+/// size.
+///
+/// Only used to reinterpret `u8`, `u32` and `u64` as `jbyte` (`i8`), `jint` (`i32`) and `jlong`
+/// (`i64`).
+macro_rules! reinterpret_as {
+    ($java_element_type:ty, $elements:expr) => {{
+        let elements = $elements;
+
+        // SAFETY: An unsigned integer type and the signed integer type with the same width have the
+        // same size and alignment, and every bit pattern is a valid value of both types, so the
+        // slice can be read as a slice of the signed type with the same length and lifetime.
+        Cow::Borrowed(unsafe {
+            slice::from_raw_parts(
+                elements.as_ptr() as *const $java_element_type,
+                elements.len(),
+            )
+        })
+    }};
+}
+
+impl_into_java_for_primitive_array!(
+    bool,
+    jboolean,
+    "[Z",
+    new_boolean_array,
+    set_boolean_array_region,
+    |_env, elements| Cow::Owned(
+        elements
+            .iter()
+            .map(|&element| element as jboolean)
+            .collect()
+    )
+);
+impl_into_java_for_primitive_array!(
+    i8,
+    jbyte,
+    "[B",
+    new_byte_array,
+    set_byte_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    i16,
+    jshort,
+    "[S",
+    new_short_array,
+    set_short_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    i32,
+    jint,
+    "[I",
+    new_int_array,
+    set_int_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    i64,
+    jlong,
+    "[J",
+    new_long_array,
+    set_long_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    f32,
+    jfloat,
+    "[F",
+    new_float_array,
+    set_float_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    f64,
+    jdouble,
+    "[D",
+    new_double_array,
+    set_double_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    u8,
+    jbyte,
+    "[B",
+    new_byte_array,
+    set_byte_array_region,
+    |_env, elements| reinterpret_as!(jbyte, elements)
+);
+impl_into_java_for_primitive_array!(
+    u16,
+    jchar,
+    "[C",
+    new_char_array,
+    set_char_array_region,
+    |_env, elements| Cow::Borrowed(elements)
+);
+impl_into_java_for_primitive_array!(
+    u32,
+    jint,
+    "[I",
+    new_int_array,
+    set_int_array_region,
+    |_env, elements| reinterpret_as!(jint, elements)
+);
+impl_into_java_for_primitive_array!(
+    u64,
+    jlong,
+    "[J",
+    new_long_array,
+    set_long_array_region,
+    |_env, elements| reinterpret_as!(jlong, elements)
+);
+impl_into_java_for_primitive_array!(
+    char,
+    jchar,
+    "[C",
+    new_char_array,
+    set_char_array_region,
+    |env, elements| Cow::Owned(
+        elements
+            .iter()
+            .map(|&element| element.try_into_java(env))
+            .collect::<Result<_, _>>()?
+    )
+);
+
+/// The JNI signature of an element type for [`IntoJava`].
+struct IntoJavaElement<'borrow, 'env, T>(PhantomData<(&'borrow (), &'env (), T)>);
+
+impl<'borrow, 'env: 'borrow, T> ElementSignature for IntoJavaElement<'borrow, 'env, T>
+where
+    T: IntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+}
+
+/// The JNI signature of an element type for [`TryIntoJava`].
+struct TryIntoJavaElement<'borrow, 'env, T>(PhantomData<(&'borrow (), &'env (), T)>);
+
+impl<'borrow, 'env: 'borrow, T> ElementSignature for TryIntoJavaElement<'borrow, 'env, T>
+where
+    T: TryIntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+}
+
+/// Returns the name of the class with the specified JNI signature, as accepted by `FindClass`.
+fn class_name_for_signature(jni_signature: &str) -> &str {
+    jni_signature
+        .strip_prefix('L')
+        .and_then(|class_name| class_name.strip_suffix(';'))
+        .unwrap_or(jni_signature)
+}
+
+/// Creates a Java object array with the class of the element signature, converting each element.
+fn try_object_array_into_java<'borrow, 'env, T>(
+    env: &'borrow JnixEnv<'env>,
+    jni_signature: &'static str,
+    element_signature: &'static str,
+    elements: impl ExactSizeIterator<Item = T>,
+    mut convert_element: impl FnMut(T) -> Result<AutoLocal<'env, 'borrow>, JnixError>,
+) -> Result<AutoLocal<'env, 'borrow>, JnixError>
+where
+    'env: 'borrow,
+{
+    let element_class = env.try_get_class(class_name_for_signature(element_signature))?;
+    let array = env
+        .new_object_array(
+            elements.len() as jsize,
+            JClass::from(element_class.as_obj()),
+            JObject::null(),
+        )
+        .for_class(env, jni_signature)?;
+    let array_object = env.auto_local(JObject::from(array));
+
+    for (index, element) in elements.enumerate() {
+        let element = convert_element(element)?;
+
+        env.set_object_array_element(array, index as jsize, element.as_obj())
+            .for_class(env, jni_signature)?;
+    }
+
+    Ok(array_object)
+}
+
+impl<'borrow, 'env, T, const N: usize> IntoJava<'borrow, 'env> for [T; N]
+where
+    'env: 'borrow,
+    T: IntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+{
+    const JNI_SIGNATURE: &'static str =
+        ArraySignature::<IntoJavaElement<'borrow, 'env, T>>::JNI_SIGNATURE;
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        try_object_array_into_java(
+            env,
+            Self::JNI_SIGNATURE,
+            T::JNI_SIGNATURE,
+            IntoIterator::into_iter(self),
+            |element| Ok(element.into_java(env)),
+        )
+        .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'borrow, 'env, T, const N: usize> TryIntoJava<'borrow, 'env> for [T; N]
+where
+    'env: 'borrow,
+    T: TryIntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+{
+    const JNI_SIGNATURE: &'static str =
+        ArraySignature::<TryIntoJavaElement<'borrow, 'env, T>>::JNI_SIGNATURE;
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        try_object_array_into_java(
+            env,
+            Self::JNI_SIGNATURE,
+            T::JNI_SIGNATURE,
+            IntoIterator::into_iter(self),
+            |element| element.try_into_java(env),
+        )
+    }
+}
+
+macro_rules! impl_into_java_for_object_array {
+    ( $( $rust_type:ty => |$elements:ident| $into_iter:expr ),* $(,)* ) => {
+        $(
+            impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for JavaArray<$rust_type>
+            where
+                'env: 'borrow,
+                T: IntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+            {
+                const JNI_SIGNATURE: &'static str =
+                    ArraySignature::<IntoJavaElement<'borrow, 'env, T>>::JNI_SIGNATURE;
+
+                type JavaType = AutoLocal<'env, 'borrow>;
+
+                fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                    try_object_array_into_java(
+                        env,
+                        Self::JNI_SIGNATURE,
+                        T::JNI_SIGNATURE,
+                        {
+                            let $elements = self.0;
+
+                            $into_iter
+                        },
+                        |element| Ok(element.into_java(env)),
+                    )
+                    .unwrap_or_else(|error| panic!("{}", error))
+                }
+            }
+
+            impl<'borrow, 'env, T> TryIntoJava<'borrow, 'env> for JavaArray<$rust_type>
+            where
+                'env: 'borrow,
+                T: TryIntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+            {
+                const JNI_SIGNATURE: &'static str =
+                    ArraySignature::<TryIntoJavaElement<'borrow, 'env, T>>::JNI_SIGNATURE;
+
+                type JavaType = AutoLocal<'env, 'borrow>;
+
+                fn try_into_java(
+                    self,
+                    env: &'borrow JnixEnv<'env>,
+                ) -> Result<Self::JavaType, JnixError> {
+                    try_object_array_into_java(
+                        env,
+                        Self::JNI_SIGNATURE,
+                        T::JNI_SIGNATURE,
+                        {
+                            let $elements = self.0;
+
+                            $into_iter
+                        },
+                        |element| element.try_into_java(env),
+                    )
+                }
+            }
+        )*
+    };
+}
+
+impl_into_java_for_object_array! {
+    Vec<T> => |elements| elements.into_iter(),
+    Box<[T]> => |elements| elements.into_vec().into_iter(),
+}
+
+impl<'borrow, 'env, T, const N: usize> IntoJava<'borrow, 'env> for JavaArray<[T; N]>
+where
+    'env: 'borrow,
+    [T; N]: IntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = <[T; N] as IntoJava<'borrow, 'env>>::JNI_SIGNATURE;
+
+    type JavaType = <[T; N] as IntoJava<'borrow, 'env>>::JavaType;
+
+    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        self.0.into_java(env)
+    }
+}
+
+impl<'borrow, 'env, T, const N: usize> TryIntoJava<'borrow, 'env> for JavaArray<[T; N]>
+where
+    'env: 'borrow,
+    [T; N]: TryIntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = <[T; N] as TryIntoJava<'borrow, 'env>>::JNI_SIGNATURE;
+
+    type JavaType = <[T; N] as TryIntoJava<'borrow, 'env>>::JavaType;
+
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        self.0.try_into_java(env)
+    }
+}
@@ -1,239 +1,541 @@
+mod array;
 mod net;
 
-use crate::{IntoJava, JnixEnv};
+use crate::{
+    boxing::{box_primitive, boxed_jni_signature, with_boxed},
+    error::JniResultExt,
+    AsJValue, CharString, Checked, CodePoint, IntoJava, JavaList, JnixEnv, JnixError, StrictString,
+    TryIntoJava, Widened,
+};
 use jni::{
-    objects::{AutoLocal, JList, JObject, JValue},
-    sys::{jboolean, jdouble, jint, jlong, jshort, jsize, JNI_FALSE, JNI_TRUE},
+    objects::{AutoLocal, JObject, JValue},
+    signature::{JavaType, Primitive},
+    sys::{jboolean, jbyte, jchar, jdouble, jfloat, jint, jlong, jshort, JNI_FALSE, JNI_TRUE},
+};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    fmt::Display,
 };
-use std::collections::HashSet;
-use std::iter::FromIterator;
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for bool {
-    const JNI_SIGNATURE: &'static str = "Z";
+macro_rules! impl_into_java_for_primitive {
+    (@fallible $rust_type:ty, $java_type:ty, $signature:expr, |$source:ident| $conversion:expr) => {
+        impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = $java_type;
 
-    type JavaType = jboolean;
+            fn try_into_java(self, _: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+                let $source = self;
 
-    fn into_java(self, _: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        if self {
-            JNI_TRUE
-        } else {
-            JNI_FALSE
+                $conversion
+            }
         }
-    }
+
+        impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for $rust_type {
+            const JNI_SIGNATURE: &'static str = $signature;
+
+            type JavaType = $java_type;
+
+            fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                TryIntoJava::try_into_java(self, env).unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+    };
+
+    ($rust_type:ty, $java_type:ty, $signature:expr, |$source:ident| $conversion:expr) => {
+        impl_into_java_for_primitive!(@fallible $rust_type, $java_type, $signature, |$source| {
+            Ok($conversion)
+        });
+    };
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for i16 {
-    const JNI_SIGNATURE: &'static str = "S";
+/// Converts an unsigned integer into a signed Java integer of the same size, failing if it's too
+/// large.
+fn checked_into_signed<U, S>(value: U, java_type: &'static str) -> Result<S, JnixError>
+where
+    U: Copy + Display,
+    S: TryFrom<U>,
+{
+    S::try_from(value).map_err(|_| {
+        JnixError::invalid_value(format!(
+            "Unsigned value {} is too large for a Java {}",
+            value, java_type
+        ))
+    })
+}
+
+impl_into_java_for_primitive!(bool, jboolean, "Z", |value| {
+    if value {
+        JNI_TRUE
+    } else {
+        JNI_FALSE
+    }
+});
+impl_into_java_for_primitive!(i16, jshort, "S", |value| value as jshort);
+impl_into_java_for_primitive!(i32, jint, "I", |value| value as jint);
+impl_into_java_for_primitive!(i64, jlong, "J", |value| value as jlong);
+impl_into_java_for_primitive!(f64, jdouble, "D", |value| value as jdouble);
+impl_into_java_for_primitive!(i8, jbyte, "B", |value| value as jbyte);
+impl_into_java_for_primitive!(f32, jfloat, "F", |value| value as jfloat);
+
+impl_into_java_for_primitive!(u8, jbyte, "B", |value| value as jbyte);
+impl_into_java_for_primitive!(u16, jchar, "C", |value| value as jchar);
+impl_into_java_for_primitive!(u32, jint, "I", |value| value as jint);
+impl_into_java_for_primitive!(u64, jlong, "J", |value| value as jlong);
+
+impl_into_java_for_primitive!(Widened<u8>, jshort, "S", |value| value.0 as jshort);
+impl_into_java_for_primitive!(Widened<u16>, jint, "I", |value| value.0 as jint);
+impl_into_java_for_primitive!(Widened<u32>, jlong, "J", |value| value.0 as jlong);
+
+impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for Widened<u64> {
+    const JNI_SIGNATURE: &'static str = "Ljava/math/BigInteger;";
 
-    type JavaType = jshort;
+    type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, _: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        self as jshort
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        let magnitude = self.0.to_be_bytes();
+        let magnitude_array = env.byte_array_from_slice(&magnitude).for_class(env, "[B")?;
+        let magnitude_object = env.auto_local(JObject::from(magnitude_array));
+        let signum = if self.0 == 0 { 0 } else { 1 };
+
+        let big_integer = env.new_cached_object(
+            "java/math/BigInteger",
+            "(I[B)V",
+            &[
+                JValue::Int(signum),
+                JValue::Object(magnitude_object.as_obj()),
+            ],
+        )?;
+
+        Ok(env.auto_local(big_integer))
     }
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for i32 {
-    const JNI_SIGNATURE: &'static str = "I";
+impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for Widened<u64> {
+    const JNI_SIGNATURE: &'static str = "Ljava/math/BigInteger;";
 
-    type JavaType = jint;
+    type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, _: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        self as jint
+    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        self.try_into_java(env)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for i64 {
-    const JNI_SIGNATURE: &'static str = "J";
+impl_into_java_for_primitive!(@fallible char, jchar, "C", |value| {
+    jchar::try_from(value as u32).map_err(|_| {
+        JnixError::invalid_value(format!(
+            "Character U+{:04X} is outside the Basic Multilingual Plane, so it needs a surrogate \
+             pair and can't be converted into a single Java char",
+            value as u32
+        ))
+    })
+});
+impl_into_java_for_primitive!(CodePoint, jint, "I", |value| value.0 as jint);
+
+impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for CharString {
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    type JavaType = jlong;
+    type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, _: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        self as jlong
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        self.0.to_string().try_into_java(env)
     }
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for f64 {
-    const JNI_SIGNATURE: &'static str = "D";
+impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for CharString {
+    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-    type JavaType = jdouble;
+    type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, _: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        self as jdouble
+    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        self.try_into_java(env)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for &'_ [u8] {
-    const JNI_SIGNATURE: &'static str = "[B";
+impl_into_java_for_primitive!(@fallible Checked<u8>, jbyte, "B", |value| {
+    checked_into_signed(value.0, "byte")
+});
+impl_into_java_for_primitive!(Checked<u16>, jchar, "C", |value| value.0 as jchar);
+impl_into_java_for_primitive!(@fallible Checked<u32>, jint, "I", |value| {
+    checked_into_signed(value.0, "int")
+});
+impl_into_java_for_primitive!(@fallible Checked<u64>, jlong, "J", |value| {
+    checked_into_signed(value.0, "long")
+});
+
+impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for Option<T>
+where
+    'env: 'borrow,
+    T: IntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        let size = self.len();
-        let array = env
-            .new_byte_array(size as jsize)
-            .expect("Failed to create a Java array of bytes");
+        match self {
+            Some(t) => t.into_java(env),
+            None => env.auto_local(JObject::null()),
+        }
+    }
+}
 
-        let data = unsafe { std::slice::from_raw_parts(self.as_ptr() as *const i8, size) };
+impl<'borrow, 'env, T> TryIntoJava<'borrow, 'env> for Option<T>
+where
+    'env: 'borrow,
+    T: TryIntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+{
+    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
 
-        env.set_byte_array_region(array, 0, data)
-            .expect("Failed to copy bytes to Java array");
+    type JavaType = AutoLocal<'env, 'borrow>;
 
-        env.auto_local(JObject::from(array))
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        match self {
+            Some(t) => t.try_into_java(env),
+            None => Ok(env.auto_local(JObject::null())),
+        }
     }
 }
 
-macro_rules! impl_into_java_for_array {
-    ($element_type:ty) => {
-        impl_into_java_for_array!(
-            $element_type,
-             0  1  2  3  4  5  6  7
-             8  9 10 11 12 13 14 15
-            16 17 18 19 20 21 22 23
-            24 25 26 27 28 29 30 31
-            32
-        );
-    };
-
-    ($element_type:ty, $( $count:tt )*) => {
+macro_rules! impl_into_java_for_boxed_primitive {
+    ( $( $rust_type:ty ),* $(,)* ) => {
         $(
-            impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for [$element_type; $count] {
-                const JNI_SIGNATURE: &'static str = "[B";
+            impl<'borrow, 'env> TryIntoJava<'borrow, 'env> for Option<$rust_type>
+            where
+                'env: 'borrow,
+            {
+                const JNI_SIGNATURE: &'static str = boxed_jni_signature(
+                    <$rust_type as TryIntoJava<'borrow, 'env>>::JNI_SIGNATURE,
+                );
+
+                type JavaType = AutoLocal<'env, 'borrow>;
+
+                fn try_into_java(
+                    self,
+                    env: &'borrow JnixEnv<'env>,
+                ) -> Result<Self::JavaType, JnixError> {
+                    match self {
+                        Some(value) => box_primitive(env, value.try_into_java(env)?.as_jvalue()),
+                        None => Ok(env.auto_local(JObject::null())),
+                    }
+                }
+            }
+
+            impl<'borrow, 'env> IntoJava<'borrow, 'env> for Option<$rust_type>
+            where
+                'env: 'borrow,
+            {
+                const JNI_SIGNATURE: &'static str = boxed_jni_signature(
+                    <$rust_type as IntoJava<'borrow, 'env>>::JNI_SIGNATURE,
+                );
 
                 type JavaType = AutoLocal<'env, 'borrow>;
 
                 fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-                    (&self as &[$element_type]).into_java(env)
+                    self.try_into_java(env)
+                        .unwrap_or_else(|error| panic!("{}", error))
                 }
             }
         )*
     };
 }
 
-impl_into_java_for_array!(u8);
+impl_into_java_for_boxed_primitive!(bool, char, i8, i16, i32, i64, f32, f64, u8, u16, u32, u64);
 
-impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for Option<T>
+fn try_array_list_into_java<'borrow, 'env, T, J>(
+    env: &'borrow JnixEnv<'env>,
+    elements: impl ExactSizeIterator<Item = T>,
+    mut convert_element: impl FnMut(T) -> Result<J, JnixError>,
+) -> Result<AutoLocal<'env, 'borrow>, JnixError>
+where
+    'env: 'borrow,
+    J: AsJValue<'env>,
+{
+    let initial_capacity = elements.len();
+    let parameters = [JValue::Int(initial_capacity as jint)];
+
+    let list_object = env.new_cached_object("java/util/ArrayList", "(I)V", &parameters)?;
+    let list_object = env.auto_local(list_object);
+    let add_return_type = JavaType::Primitive(Primitive::Boolean);
+
+    for element in elements {
+        let element = convert_element(element)?;
+
+        with_boxed(env, element.as_jvalue(), |object| {
+            env.call_cached_method(
+                list_object.as_obj(),
+                "java/util/ArrayList",
+                "add",
+                "(Ljava/lang/Object;)Z",
+                add_return_type.clone(),
+                &[JValue::Object(object)],
+            )
+        })?;
+    }
+
+    Ok(list_object)
+}
+
+impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for Vec<T>
 where
     'env: 'borrow,
     T: IntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
 {
-    const JNI_SIGNATURE: &'static str = T::JNI_SIGNATURE;
+    const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        match self {
-            Some(t) => t.into_java(env),
-            None => env.auto_local(JObject::null()),
-        }
+        try_array_list_into_java(env, self.into_iter(), |element| Ok(element.into_java(env)))
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'borrow, 'env> IntoJava<'borrow, 'env> for Option<bool>
+impl<'borrow, 'env, T> TryIntoJava<'borrow, 'env> for Vec<T>
 where
     'env: 'borrow,
+    T: TryIntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
 {
-    const JNI_SIGNATURE: &'static str = "Ljava/lang/Boolean;";
+    const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        match self {
-            Some(value) => {
-                let class = env.get_class("java/lang/Boolean");
-                let boxed_boolean = env
-                    .new_object(&class, "(Z)V", &[JValue::Bool(value as jboolean)])
-                    .expect("Failed to create boxed Boolean object");
-
-                env.auto_local(boxed_boolean)
-            }
-            None => env.auto_local(JObject::null()),
-        }
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        try_array_list_into_java(env, self.into_iter(), |element| element.try_into_java(env))
     }
 }
 
-impl<'borrow, 'env> IntoJava<'borrow, 'env> for Option<i32>
+impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for JavaList<Vec<T>>
 where
     'env: 'borrow,
+    T: IntoJava<'borrow, 'env>,
 {
-    const JNI_SIGNATURE: &'static str = "Ljava/lang/Integer;";
+    const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        match self {
-            Some(value) => {
-                let class = env.get_class("java/lang/Integer");
-                let boxed_boolean = env
-                    .new_object(&class, "(I)V", &[JValue::Int(value as jint)])
-                    .expect("Failed to create boxed Integer object");
-
-                env.auto_local(boxed_boolean)
-            }
-            None => env.auto_local(JObject::null()),
-        }
+        try_array_list_into_java(
+            env,
+            self.0.into_iter(),
+            |element| Ok(element.into_java(env)),
+        )
+        .unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for Vec<T>
+impl<'borrow, 'env, T> TryIntoJava<'borrow, 'env> for JavaList<Vec<T>>
 where
     'env: 'borrow,
-    T: IntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+    T: TryIntoJava<'borrow, 'env>,
 {
     const JNI_SIGNATURE: &'static str = "Ljava/util/ArrayList;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        let initial_capacity = self.len();
-        let parameters = [JValue::Int(initial_capacity as jint)];
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        try_array_list_into_java(env, self.0.into_iter(), |element| {
+            element.try_into_java(env)
+        })
+    }
+}
 
-        let class = env.get_class("java/util/ArrayList");
-        let list_object = env
-            .new_object(&class, "(I)V", &parameters)
-            .expect("Failed to create ArrayList object");
+macro_rules! impl_into_java_for_string {
+    ( $( $rust_type:ty => |$source:ident| $string:expr ),* $(,)* ) => {
+        $(
+            impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for $rust_type {
+                const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
 
-        let list =
-            JList::from_env(env, list_object).expect("Failed to create JList from ArrayList");
+                type JavaType = AutoLocal<'env, 'borrow>;
 
-        for element in self {
-            list.add(element.into_java(env).as_obj())
-                .expect("Failed to add element to ArrayList");
-        }
+                fn try_into_java(
+                    self,
+                    env: &'borrow JnixEnv<'env>,
+                ) -> Result<Self::JavaType, JnixError> {
+                    let $source = self;
+                    let jstring = env.new_utf16_string($string)?;
 
-        env.auto_local(list_object)
-    }
+                    Ok(env.auto_local(jstring))
+                }
+            }
+
+            impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for $rust_type {
+                const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
+
+                type JavaType = AutoLocal<'env, 'borrow>;
+
+                fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                    self.try_into_java(env)
+                        .unwrap_or_else(|error| panic!("{}", error))
+                }
+            }
+        )*
+    };
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for String {
-    const JNI_SIGNATURE: &'static str = "Ljava/lang/String;";
+impl_into_java_for_string! {
+    String => |value| &value,
+    &'_ str => |value| value,
+    Cow<'_, str> => |value| &value,
+    StrictString => |value| &value.0,
+}
 
-    type JavaType = AutoLocal<'env, 'borrow>;
+fn try_hash_set_into_java<'borrow, 'env, T, J>(
+    env: &'borrow JnixEnv<'env>,
+    elements: HashSet<T>,
+    convert_element: impl FnMut(T) -> Result<J, JnixError>,
+) -> Result<AutoLocal<'env, 'borrow>, JnixError>
+where
+    'env: 'borrow,
+    J: AsJValue<'env>,
+{
+    let list_object = try_array_list_into_java(env, elements.into_iter(), convert_element)?;
 
-    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        let jstring = env.new_string(&self).expect("Failed to create Java String");
+    let hash_object = env.new_cached_object(
+        "java/util/HashSet",
+        "(Ljava/util/Collection;)V",
+        &[JValue::from(&list_object)],
+    )?;
 
-        env.auto_local(jstring)
-    }
+    Ok(env.auto_local(hash_object))
 }
 
 impl<'borrow, 'env, T> IntoJava<'borrow, 'env> for HashSet<T>
 where
     'env: 'borrow,
-    T: IntoJava<'borrow, 'env, JavaType = AutoLocal<'env, 'borrow>>,
+    T: IntoJava<'borrow, 'env>,
 {
     const JNI_SIGNATURE: &'static str = "Ljava/util/HashSet;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        let vector = Vec::from_iter(self);
-        let list_object = vector.into_java(&env);
+        try_hash_set_into_java(env, self, |element| Ok(element.into_java(env)))
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'borrow, 'env, T> TryIntoJava<'borrow, 'env> for HashSet<T>
+where
+    'env: 'borrow,
+    T: TryIntoJava<'borrow, 'env>,
+{
+    const JNI_SIGNATURE: &'static str = "Ljava/util/HashSet;";
 
-        let hash_class = env.get_class("java/util/HashSet");
-        let hash_object = env
-            .new_object(&hash_class, "(Ljava/util/Collection;)V", &[JValue::from(&list_object)])
-            .expect("Failed to create HashSet object");
+    type JavaType = AutoLocal<'env, 'borrow>;
 
-        env.auto_local(hash_object)
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        try_hash_set_into_java(env, self, |element| element.try_into_java(env))
     }
 }
+
+/// Creates a Java map of the specified class and puts the converted entries into it, boxing
+/// primitive keys and values.
+///
+/// The class must implement `java.util.Map` and have a constructor that receives the initial
+/// capacity.
+pub(crate) fn try_map_into_java<'borrow, 'env, K, V, JK, JV>(
+    env: &'borrow JnixEnv<'env>,
+    class_name: &'static str,
+    entries: impl ExactSizeIterator<Item = (K, V)>,
+    mut convert_entry: impl FnMut(K, V) -> Result<(JK, JV), JnixError>,
+) -> Result<AutoLocal<'env, 'borrow>, JnixError>
+where
+    'env: 'borrow,
+    JK: AsJValue<'env>,
+    JV: AsJValue<'env>,
+{
+    // Avoid resizing the map, which has a load factor of 0.75
+    let initial_capacity = entries.len() / 3 * 4 + 4;
+    let parameters = [JValue::Int(initial_capacity.min(jint::MAX as usize) as jint)];
+
+    let map_object = env.new_cached_object(class_name, "(I)V", &parameters)?;
+    let map_object = env.auto_local(map_object);
+    let put_return_type = JavaType::Object("java/lang/Object".to_owned());
+
+    for (key, value) in entries {
+        let (key, value) = convert_entry(key, value)?;
+
+        let previous_value = with_boxed(env, key.as_jvalue(), |key_object| {
+            with_boxed(env, value.as_jvalue(), |value_object| {
+                env.call_cached_method(
+                    map_object.as_obj(),
+                    "java/util/Map",
+                    "put",
+                    "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+                    put_return_type.clone(),
+                    &[JValue::Object(key_object), JValue::Object(value_object)],
+                )?
+                .l()
+                .for_member(
+                    env,
+                    "java/util/Map",
+                    "put",
+                    "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/lang/Object;",
+                )
+            })
+        })?;
+
+        let _ = env.delete_local_ref(previous_value);
+    }
+
+    Ok(map_object)
+}
+
+/// Implements `IntoJava` and `TryIntoJava` for a Rust map type, creating a Java map of the
+/// specified class.
+///
+/// The signature is `java.util.Map`, like the one of the `FromJava` implementations, so that a map
+/// can be converted in both directions through the same Java type.
+macro_rules! impl_into_java_for_map {
+    ( $( $rust_type:ident => $class_name:expr ),* $(,)* ) => {
+        $(
+            impl<'borrow, 'env, K, V> IntoJava<'borrow, 'env> for $rust_type<K, V>
+            where
+                'env: 'borrow,
+                K: IntoJava<'borrow, 'env>,
+                V: IntoJava<'borrow, 'env>,
+            {
+                const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+                type JavaType = AutoLocal<'env, 'borrow>;
+
+                fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+                    try_map_into_java(env, $class_name, self.into_iter(), |key, value| {
+                        Ok((key.into_java(env), value.into_java(env)))
+                    })
+                    .unwrap_or_else(|error| panic!("{}", error))
+                }
+            }
+
+            impl<'borrow, 'env, K, V> TryIntoJava<'borrow, 'env> for $rust_type<K, V>
+            where
+                'env: 'borrow,
+                K: TryIntoJava<'borrow, 'env>,
+                V: TryIntoJava<'borrow, 'env>,
+            {
+                const JNI_SIGNATURE: &'static str = "Ljava/util/Map;";
+
+                type JavaType = AutoLocal<'env, 'borrow>;
+
+                fn try_into_java(
+                    self,
+                    env: &'borrow JnixEnv<'env>,
+                ) -> Result<Self::JavaType, JnixError> {
+                    try_map_into_java(env, $class_name, self.into_iter(), |key, value| {
+                        Ok((key.try_into_java(env)?, value.try_into_java(env)?))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_into_java_for_map! {
+    HashMap => "java/util/HashMap",
+    BTreeMap => "java/util/LinkedHashMap",
+}
@@ -1,4 +1,4 @@
-use crate::{AsJValue, IntoJava, JnixEnv};
+use crate::{error::JniResultExt, AsJValue, IntoJava, JnixEnv, JnixError, TryIntoJava};
 use jni::{
     objects::{AutoLocal, JObject, JValue},
     signature::JavaType,
@@ -9,42 +9,41 @@ use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 fn ipvx_addr_into_java<'borrow, 'env: 'borrow>(
     original_octets: &[u8],
     env: &'borrow JnixEnv<'env>,
-) -> AutoLocal<'env, 'borrow> {
-    let class = env.get_class("java/net/InetAddress");
-    let constructor = env
-        .get_static_method_id(&class, "getByAddress", "([B)Ljava/net/InetAddress;")
-        .expect("Failed to get InetAddress.getByAddress method ID");
-
+) -> Result<AutoLocal<'env, 'borrow>, JnixError> {
     let octets_array = env
         .new_byte_array(original_octets.len() as i32)
-        .expect("Failed to create byte array to store IP address");
+        .for_class(env, "[B")?;
+    let octets = env.auto_local(JObject::from(octets_array));
 
-    let octet_data: Vec<i8> = original_octets
-        .into_iter()
-        .map(|octet| *octet as i8)
-        .collect();
+    let octet_data: Vec<i8> = original_octets.iter().map(|octet| *octet as i8).collect();
 
     env.set_byte_array_region(octets_array, 0, &octet_data)
-        .expect("Failed to copy IP address octets to byte array");
+        .for_class(env, "[B")?;
 
-    let octets = env.auto_local(JObject::from(octets_array));
-    let result = env
-        .call_static_method_unchecked(
-            &class,
-            constructor,
-            JavaType::Object("java/net/InetAddress".to_owned()),
-            &[octets.as_jvalue()],
-        )
-        .expect("Failed to create InetAddress Java object");
+    let result = env.call_cached_static_method(
+        "java/net/InetAddress",
+        "getByAddress",
+        "([B)Ljava/net/InetAddress;",
+        JavaType::Object("java/net/InetAddress".to_owned()),
+        &[octets.as_jvalue()],
+    )?;
 
     match result {
-        JValue::Object(object) => env.auto_local(object),
-        value => {
-            panic!(
-                "InetAddress.getByAddress returned an invalid value: {:?}",
-                value
-            );
-        }
+        JValue::Object(object) => Ok(env.auto_local(object)),
+        value => Err(JnixError::invalid_value(format!(
+            "InetAddress.getByAddress returned an invalid value: {:?}",
+            value
+        ))),
+    }
+}
+
+impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for Ipv4Addr {
+    const JNI_SIGNATURE: &'static str = "Ljava/net/InetAddress;";
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        ipvx_addr_into_java(self.octets().as_ref(), env)
     }
 }
 
@@ -54,6 +53,17 @@ impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for Ipv4Addr {
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        self.try_into_java(env)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for Ipv6Addr {
+    const JNI_SIGNATURE: &'static str = "Ljava/net/InetAddress;";
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
         ipvx_addr_into_java(self.octets().as_ref(), env)
     }
 }
@@ -64,38 +74,62 @@ impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for Ipv6Addr {
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        ipvx_addr_into_java(self.octets().as_ref(), env)
+        self.try_into_java(env)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for IpAddr {
+impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for IpAddr {
     const JNI_SIGNATURE: &'static str = "Ljava/net/InetAddress;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
-    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
         match self {
-            IpAddr::V4(address) => address.into_java(env),
-            IpAddr::V6(address) => address.into_java(env),
+            IpAddr::V4(address) => address.try_into_java(env),
+            IpAddr::V6(address) => address.try_into_java(env),
         }
     }
 }
 
-impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for SocketAddr {
-    const JNI_SIGNATURE: &'static str = "Ljava/net/InetSocketAddress;";
+impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for IpAddr {
+    const JNI_SIGNATURE: &'static str = "Ljava/net/InetAddress;";
 
     type JavaType = AutoLocal<'env, 'borrow>;
 
     fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
-        let ip_address = self.ip().into_java(env);
+        self.try_into_java(env)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+impl<'borrow, 'env: 'borrow> TryIntoJava<'borrow, 'env> for SocketAddr {
+    const JNI_SIGNATURE: &'static str = "Ljava/net/InetSocketAddress;";
+
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError> {
+        let ip_address = self.ip().try_into_java(env)?;
         let port = self.port() as jint;
         let parameters = [JValue::Object(ip_address.as_obj()), JValue::Int(port)];
 
-        let class = env.get_class("java/net/InetSocketAddress");
-        let object = env
-            .new_object(&class, "(Ljava/net/InetAddress;I)V", &parameters)
-            .expect("Failed to convert SocketAddr Rust type into InetSocketAddress Java object");
+        let object = env.new_cached_object(
+            "java/net/InetSocketAddress",
+            "(Ljava/net/InetAddress;I)V",
+            &parameters,
+        )?;
+
+        Ok(env.auto_local(object))
+    }
+}
+
+impl<'borrow, 'env: 'borrow> IntoJava<'borrow, 'env> for SocketAddr {
+    const JNI_SIGNATURE: &'static str = "Ljava/net/InetSocketAddress;";
 
-        env.auto_local(object)
+    type JavaType = AutoLocal<'env, 'borrow>;
+
+    fn into_java(self, env: &'borrow JnixEnv<'env>) -> Self::JavaType {
+        self.try_into_java(env)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 }
@@ -1,10 +1,12 @@
 mod implementations;
 
-use crate::{AsJValue, JnixEnv};
+use crate::{AsJValue, JnixEnv, JnixError};
 
 /// Conversion from a type into its Java representation.
 ///
 /// A type that implements this trait has an equivalent Java representation.
+///
+/// The conversion panics if it fails. See [`TryIntoJava`] for a fallible version.
 pub trait IntoJava<'borrow, 'env: 'borrow> {
     /// The [JNI signature] of the Java type.
     ///
@@ -25,3 +27,27 @@ pub trait IntoJava<'borrow, 'env: 'borrow> {
         Self::JNI_SIGNATURE
     }
 }
+
+/// Fallible conversion from a type into its Java representation.
+///
+/// A type that implements this trait has an equivalent Java representation.
+pub trait TryIntoJava<'borrow, 'env: 'borrow> {
+    /// The [JNI signature] of the Java type.
+    ///
+    /// [JNI signature]: https://docs.oracle.com/javase/7/docs/technotes/guides/jni/spec/types.html#wp16432
+    const JNI_SIGNATURE: &'static str;
+
+    /// The Rust type that wraps a reference to the Java type.
+    type JavaType: AsJValue<'env>;
+
+    /// Performs the conversion, returning an error if it fails.
+    fn try_into_java(self, env: &'borrow JnixEnv<'env>) -> Result<Self::JavaType, JnixError>;
+
+    /// Returns the JNI signature of the Java type.
+    ///
+    /// This is a helper method to obtain the `JNI_SIGNATURE` from a value without knowing its
+    /// exact type name.
+    fn jni_signature(&self) -> &'static str {
+        Self::JNI_SIGNATURE
+    }
+}
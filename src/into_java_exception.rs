@@ -0,0 +1,163 @@
+use crate::{error::JniResultExt, JavaException, JnixEnv, JnixError};
+use jni::objects::{JObject, JThrowable, JValue};
+use std::borrow::Cow;
+
+/// Conversion of a Rust error into a Java exception.
+///
+/// A type that implements this trait can be thrown as a Java exception, usually at the boundary of
+/// a native method called from Java. The exception object is created by calling the exception
+/// class's constructor that receives the message as a `String`.
+pub trait IntoJavaException {
+    /// Returns the name of the Java exception class, in the JNI format (e.g.,
+    /// `java/lang/IllegalStateException`).
+    fn exception_class_name(&self) -> Cow<'_, str>;
+
+    /// Returns the message to use in the Java exception.
+    fn exception_message(&self) -> String;
+
+    /// Creates the Java exception object.
+    ///
+    /// The exception class is obtained from the [`JnixEnv`] class cache. The `cause` is a Java
+    /// exception that was pending when this exception was thrown, and it is used as the cause of
+    /// the created exception.
+    fn to_java_exception<'env>(
+        &self,
+        env: &JnixEnv<'env>,
+        cause: Option<&JavaException>,
+    ) -> Result<JThrowable<'env>, JnixError> {
+        new_throwable(
+            env,
+            &self.exception_class_name(),
+            &self.exception_message(),
+            cause_object(cause),
+        )
+    }
+
+    /// Creates the Java exception object and throws it.
+    ///
+    /// A Java exception that is still pending in the JNI environment (for example, because a JNI
+    /// call failed) is taken first, since no other JNI calls can be made while it is pending, and
+    /// it is used as the cause of the thrown exception.
+    ///
+    /// The exception will be pending in the JNI environment after this returns successfully, so
+    /// the native method should return as soon as possible.
+    fn throw(&self, env: &JnixEnv) -> Result<(), JnixError> {
+        let pending_exception = env.take_exception();
+        let throwable = self.to_java_exception(env, pending_exception.as_ref())?;
+        let result = env
+            .throw(throwable)
+            .for_class(env, self.exception_class_name().into_owned());
+
+        let _ = env.delete_local_ref(JObject::from(throwable));
+
+        result
+    }
+}
+
+impl IntoJavaException for JavaException {
+    fn exception_class_name(&self) -> Cow<'_, str> {
+        Cow::Owned(self.class_name().replace('.', "/"))
+    }
+
+    fn exception_message(&self) -> String {
+        self.message().unwrap_or_default().to_owned()
+    }
+
+    /// Returns the original exception object if it's still available, so that it is rethrown
+    /// unchanged.
+    fn to_java_exception<'env>(
+        &self,
+        env: &JnixEnv<'env>,
+        cause: Option<&JavaException>,
+    ) -> Result<JThrowable<'env>, JnixError> {
+        match self.throwable() {
+            Some(throwable) => {
+                let throwable = JObject::from(throwable.as_obj().into_inner());
+                let local_ref = env
+                    .new_local_ref::<JObject>(throwable)
+                    .for_class(env, self.class_name().to_owned())?;
+
+                Ok(JThrowable::from(local_ref))
+            }
+            None => new_throwable(
+                env,
+                &self.exception_class_name(),
+                &self.exception_message(),
+                cause_object(cause),
+            ),
+        }
+    }
+}
+
+/// Converts the error into a `java.lang.RuntimeException`.
+///
+/// If the error was caused by a Java exception, it is used as the cause of the thrown exception
+/// instead of the pending exception.
+impl IntoJavaException for JnixError {
+    fn exception_class_name(&self) -> Cow<'_, str> {
+        Cow::Borrowed("java/lang/RuntimeException")
+    }
+
+    fn exception_message(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_java_exception<'env>(
+        &self,
+        env: &JnixEnv<'env>,
+        cause: Option<&JavaException>,
+    ) -> Result<JThrowable<'env>, JnixError> {
+        let cause = self
+            .java_exception()
+            .filter(|exception| exception.throwable().is_some())
+            .or(cause);
+
+        new_throwable(
+            env,
+            &self.exception_class_name(),
+            &self.exception_message(),
+            cause_object(cause),
+        )
+    }
+}
+
+/// Returns the exception object to use as a cause, or a null object if there's none.
+pub(crate) fn cause_object(cause: Option<&JavaException>) -> JObject<'_> {
+    cause
+        .and_then(JavaException::throwable)
+        .map(|throwable| throwable.as_obj())
+        .unwrap_or_else(JObject::null)
+}
+
+/// Creates a Java exception object with a message and an optional cause.
+pub(crate) fn new_throwable<'env>(
+    env: &JnixEnv<'env>,
+    class_name: &str,
+    message: &str,
+    cause: JObject,
+) -> Result<JThrowable<'env>, JnixError> {
+    let class = env.try_get_class(class_name)?;
+    let message = env.new_utf16_string(message)?;
+    let message = env.auto_local(message);
+
+    let (signature, parameters) = if cause.is_null() {
+        (
+            "(Ljava/lang/String;)V",
+            vec![JValue::Object(message.as_obj())],
+        )
+    } else {
+        (
+            "(Ljava/lang/String;Ljava/lang/Throwable;)V",
+            vec![JValue::Object(message.as_obj()), JValue::Object(cause)],
+        )
+    };
+
+    let throwable = env.new_object(&class, signature, &parameters).for_member(
+        env,
+        class_name.to_owned(),
+        "<init>",
+        signature,
+    )?;
+
+    Ok(JThrowable::from(throwable))
+}
@@ -0,0 +1,247 @@
+use crate::{JnixEnv, LoneSurrogates};
+use jni::{
+    objects::{GlobalRef, JObject, JString},
+    sys::jobjectArray,
+};
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+/// Limit on how many causes are followed when capturing an exception, to avoid looping forever if
+/// there is a cycle in the cause chain.
+const MAX_CAUSE_DEPTH: usize = 32;
+
+/// A Java exception that was thrown while executing JNI code.
+///
+/// The exception is captured and cleared from the JNI environment, so that it no longer affects
+/// later JNI calls. A global reference to the original `Throwable` object is kept, so that it can
+/// be rethrown later if necessary.
+#[derive(Clone)]
+pub struct JavaException {
+    class_name: String,
+    message: Option<String>,
+    stack_trace: Vec<StackTraceElement>,
+    cause: Option<Box<JavaException>>,
+    throwable: Option<GlobalRef>,
+}
+
+/// An element of a Java exception's stack trace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackTraceElement {
+    /// The fully qualified name of the class containing the execution point.
+    pub class_name: String,
+
+    /// The name of the method containing the execution point.
+    pub method_name: String,
+
+    /// The name of the source file containing the execution point, if it is available.
+    pub file_name: Option<String>,
+
+    /// The line number of the execution point, or a negative number if it is unavailable.
+    pub line_number: i32,
+}
+
+impl JavaException {
+    /// Reads the information of a `Throwable` object.
+    ///
+    /// There must not be a pending exception when this is called. If reading some of the
+    /// information fails, it is left out.
+    pub(crate) fn from_throwable(env: &JnixEnv, throwable: JObject) -> Self {
+        Self::from_throwable_with_depth(env, throwable, 0)
+    }
+
+    fn from_throwable_with_depth(env: &JnixEnv, throwable: JObject, depth: usize) -> Self {
+        let class_name = clear_exception_on_error(env, read_class_name(env, throwable))
+            .unwrap_or_else(|| "java.lang.Throwable".to_owned());
+        let message = read_optional_string(env, throwable, "getMessage");
+        let stack_trace =
+            clear_exception_on_error(env, read_stack_trace(env, throwable)).unwrap_or_default();
+
+        let cause = if depth < MAX_CAUSE_DEPTH {
+            read_cause(env, throwable).map(|cause| {
+                let cause_exception = Self::from_throwable_with_depth(env, cause, depth + 1);
+                let _ = env.delete_local_ref(cause);
+
+                Box::new(cause_exception)
+            })
+        } else {
+            None
+        };
+
+        JavaException {
+            class_name,
+            message,
+            stack_trace,
+            cause,
+            throwable: env.new_global_ref(throwable).ok(),
+        }
+    }
+
+    /// Returns the fully qualified name of the exception's class.
+    pub fn class_name(&self) -> &str {
+        &self.class_name
+    }
+
+    /// Returns the exception's message, if it has one.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Returns the elements of the exception's stack trace, starting from the innermost frame.
+    pub fn stack_trace(&self) -> &[StackTraceElement] {
+        &self.stack_trace
+    }
+
+    /// Returns the exception that caused this exception, if there is one.
+    pub fn cause(&self) -> Option<&JavaException> {
+        self.cause.as_deref()
+    }
+
+    /// Returns a global reference to the original `Throwable` object, if one could be created.
+    pub fn throwable(&self) -> Option<&GlobalRef> {
+        self.throwable.as_ref()
+    }
+}
+
+fn clear_exception_on_error<T>(env: &JnixEnv, result: jni::errors::Result<T>) -> Option<T> {
+    if result.is_err() {
+        let _ = env.exception_clear();
+    }
+
+    result.ok()
+}
+
+fn read_class_name(env: &JnixEnv, throwable: JObject) -> jni::errors::Result<String> {
+    let class = env.auto_local(env.get_object_class(throwable)?);
+    let name = env
+        .call_method(class.as_obj(), "getName", "()Ljava/lang/String;", &[])?
+        .l()?;
+    let name = env.auto_local(name);
+    let class_name = env.get_string(JString::from(name.as_obj()))?.into();
+
+    Ok(class_name)
+}
+
+fn read_optional_string(env: &JnixEnv, object: JObject, getter: &str) -> Option<String> {
+    let result = env
+        .call_method(object, getter, "()Ljava/lang/String;", &[])
+        .and_then(|value| value.l());
+    let string = clear_exception_on_error(env, result)?;
+
+    if string.is_null() {
+        return None;
+    }
+
+    let string = env.auto_local(string);
+
+    env.get_utf16_string(JString::from(string.as_obj()), LoneSurrogates::Replace)
+        .ok()
+}
+
+fn read_cause<'env>(env: &JnixEnv<'env>, throwable: JObject<'env>) -> Option<JObject<'env>> {
+    let result = env
+        .call_method(throwable, "getCause", "()Ljava/lang/Throwable;", &[])
+        .and_then(|value| value.l());
+    let cause = clear_exception_on_error(env, result)?;
+
+    if cause.is_null() {
+        None
+    } else {
+        Some(cause)
+    }
+}
+
+fn read_stack_trace(
+    env: &JnixEnv,
+    throwable: JObject,
+) -> jni::errors::Result<Vec<StackTraceElement>> {
+    let result = env
+        .call_method(
+            throwable,
+            "getStackTrace",
+            "()[Ljava/lang/StackTraceElement;",
+            &[],
+        )
+        .and_then(|value| value.l());
+    let elements = match clear_exception_on_error(env, result) {
+        Some(elements) if !elements.is_null() => env.auto_local(elements),
+        _ => return Ok(Vec::new()),
+    };
+    let elements_array = elements.as_obj().into_inner() as jobjectArray;
+    let element_count = env.get_array_length(elements_array)?;
+    let mut stack_trace = Vec::with_capacity(element_count as usize);
+
+    for index in 0..element_count {
+        let element = env.auto_local(env.get_object_array_element(elements_array, index)?);
+
+        if let Some(element) = read_stack_trace_element(env, element.as_obj()) {
+            stack_trace.push(element);
+        }
+    }
+
+    Ok(stack_trace)
+}
+
+fn read_stack_trace_element(env: &JnixEnv, element: JObject) -> Option<StackTraceElement> {
+    let line_number = env
+        .call_method(element, "getLineNumber", "()I", &[])
+        .and_then(|value| value.i());
+
+    Some(StackTraceElement {
+        class_name: read_optional_string(env, element, "getClassName")?,
+        method_name: read_optional_string(env, element, "getMethodName")?,
+        file_name: read_optional_string(env, element, "getFileName"),
+        line_number: clear_exception_on_error(env, line_number).unwrap_or(-1),
+    })
+}
+
+impl Debug for JavaException {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("JavaException")
+            .field("class_name", &self.class_name)
+            .field("message", &self.message)
+            .field("stack_trace", &self.stack_trace)
+            .field("cause", &self.cause)
+            .finish()
+    }
+}
+
+impl Display for JavaException {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.class_name)?;
+
+        if let Some(message) = &self.message {
+            write!(formatter, ": {}", message)?;
+        }
+
+        if let Some(cause) = &self.cause {
+            write!(formatter, " (caused by {})", cause)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for StackTraceElement {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}.{}", self.class_name, self.method_name)?;
+
+        match (&self.file_name, self.line_number) {
+            (Some(file_name), line_number) if line_number >= 0 => {
+                write!(formatter, "({}:{})", file_name, line_number)
+            }
+            (Some(file_name), _) => write!(formatter, "({})", file_name),
+            (None, _) => write!(formatter, "(Unknown Source)"),
+        }
+    }
+}
+
+impl Error for JavaException {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn Error + 'static))
+    }
+}
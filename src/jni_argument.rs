@@ -0,0 +1,21 @@
+use crate::FromJava;
+use jni::objects::JObject;
+
+/// Selection of the raw JNI type used to receive a Rust type as an argument of a native method.
+///
+/// Native methods exported with `#[jnix::export]` receive each argument as this raw type, and then
+/// convert it using [`FromJava`]. Types that are converted from Java objects are received as a
+/// [`JObject`], and types that are converted from Java primitives (including wrappers like
+/// [`Widened`][crate::Widened] and [`CodePoint`][crate::CodePoint]) are received as the respective
+/// JNI primitive type.
+pub trait JniArgument<'env> {
+    /// The raw JNI type that is received from the JVM.
+    type JniType;
+}
+
+impl<'env, T> JniArgument<'env> for T
+where
+    T: FromJava<'env, JObject<'env>>,
+{
+    type JniType = JObject<'env>;
+}
@@ -0,0 +1,81 @@
+use jni::{
+    objects::{AutoLocal, JObject},
+    sys::jobject,
+};
+use std::ptr;
+
+/// Conversion of a Java value into the raw value returned from a native method.
+///
+/// This is implemented for the Java types produced by [`IntoJava`][crate::IntoJava], so that a
+/// converted value can be returned directly from an `extern "system" fn Java_...` entry point.
+pub trait JniReturnValue {
+    /// The raw JNI type that is returned to the JVM.
+    type JniType;
+
+    /// Converts the value into its raw JNI representation.
+    ///
+    /// Object references are leaked so that the JVM can take ownership of them.
+    fn into_jni_return_value(self) -> Self::JniType;
+
+    /// Returns the value used when the native method throws an exception instead of returning.
+    ///
+    /// The JVM ignores the returned value if an exception is pending, so this is `null` for object
+    /// types and zero for primitive types.
+    fn default_jni_return_value() -> Self::JniType;
+}
+
+impl<'env_borrow, 'env: 'env_borrow> JniReturnValue for AutoLocal<'env, 'env_borrow> {
+    type JniType = jobject;
+
+    fn into_jni_return_value(self) -> Self::JniType {
+        self.forget().into_inner()
+    }
+
+    fn default_jni_return_value() -> Self::JniType {
+        JObject::null().into_inner()
+    }
+}
+
+impl<'env> JniReturnValue for JObject<'env> {
+    type JniType = jobject;
+
+    fn into_jni_return_value(self) -> Self::JniType {
+        self.into_inner()
+    }
+
+    fn default_jni_return_value() -> Self::JniType {
+        JObject::null().into_inner()
+    }
+}
+
+impl JniReturnValue for jobject {
+    type JniType = jobject;
+
+    fn into_jni_return_value(self) -> Self::JniType {
+        self
+    }
+
+    fn default_jni_return_value() -> Self::JniType {
+        ptr::null_mut()
+    }
+}
+
+macro_rules! impl_for_primitives {
+    ( $( $primitive:ty ),* $(,)* ) => {
+        $(
+            impl JniReturnValue for $primitive {
+                type JniType = $primitive;
+
+                fn into_jni_return_value(self) -> Self::JniType {
+                    self
+                }
+
+                fn default_jni_return_value() -> Self::JniType {
+                    Self::default()
+                }
+            }
+        )*
+    };
+}
+
+impl_for_primitives!((), u8, i8, u16, i16, i32, i64, f32, f64);
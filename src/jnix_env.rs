@@ -1,24 +1,46 @@
+use crate::{
+    class_cache::{read_class_cache, remove_class_cache, write_class_cache, CachedClass, VmKey},
+    derived_classes::derived_class_names,
+    error::JniResultExt,
+    member_id_cache::{clear_member_ids, get_or_resolve_member_id, MemberKind},
+    native_methods::registered_native_methods,
+    panic::{clear_panic_location, install_panic_location_hook, PanicException},
+    ClassSource, IntoJava, IntoJavaException, JavaException, JniReturnValue, JnixError,
+    JnixErrorKind, LoneSurrogates, NativeMethod, PreloadReport, RegisterNativesError,
+};
 use jni::{
-    objects::{GlobalRef, JObject},
-    JNIEnv,
+    objects::{
+        GlobalRef, JFieldID, JMethodID, JObject, JStaticFieldID, JStaticMethodID, JString, JValue,
+    },
+    signature::JavaType,
+    sys::{jfieldID, jmethodID, jsize},
+    JNIEnv, JavaVM,
+};
+use once_cell::unsync::OnceCell;
+use std::{
+    borrow::Cow,
+    collections::hash_map::Entry,
+    ffi::c_void,
+    mem,
+    ops::Deref,
+    panic::{self, AssertUnwindSafe},
 };
-use once_cell::sync::Lazy;
-use parking_lot::Mutex;
-use std::{borrow::Cow, collections::HashMap, ops::Deref};
-
-static CLASS_CACHE: Lazy<Mutex<HashMap<String, GlobalRef>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Provides access to JNI functions.
 ///
-/// Also provides an internal cache for loaded classes.
+/// Also provides an internal cache for loaded classes, and for the method IDs and field IDs of
+/// those classes. A separate cache is kept for each Java VM.
 pub struct JnixEnv<'env> {
     env: JNIEnv<'env>,
+    vm: OnceCell<VmKey>,
 }
 
 impl<'env> From<JNIEnv<'env>> for JnixEnv<'env> {
     fn from(env: JNIEnv<'env>) -> Self {
-        JnixEnv { env }
+        JnixEnv {
+            env,
+            vm: OnceCell::new(),
+        }
     }
 }
 
@@ -41,41 +63,878 @@ impl<'env> JnixEnv<'env> {
     /// This method will panic if the class can't be loaded or if a global reference can't be
     /// obtained to the Class object.
     pub fn get_class<'a>(&self, class_name: impl Into<Cow<'a, str>>) -> GlobalRef {
+        self.try_get_class(class_name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Retrieves a global reference to a Class object, returning an error if it can't be loaded.
+    ///
+    /// Tries to retrieve it from the class cache. If it's not present in the cache, it is loaded
+    /// into the cache first.
+    ///
+    /// Classes are loaded with `FindClass`. If that fails and an application class loader was set
+    /// with [`JnixEnv::set_class_loader`], the class is loaded with `ClassLoader.loadClass`
+    /// instead.
+    pub fn try_get_class<'a>(
+        &self,
+        class_name: impl Into<Cow<'a, str>>,
+    ) -> Result<GlobalRef, JnixError> {
         let class_name = class_name.into();
-        let mut cache = CLASS_CACHE.lock();
+        let vm = self.vm_key()?;
+
+        let cached_class = read_class_cache(vm, |cache| {
+            let cached_class = cache?.classes.get(class_name.as_ref())?;
+
+            Some(cached_class.class.clone())
+        });
+
+        match cached_class {
+            Some(class) => Ok(class),
+            None => self.load_and_cache_class(vm, class_name),
+        }
+    }
+
+    /// Returns how a class in the class cache was loaded, or `None` if the class isn't cached.
+    pub fn class_source(&self, class_name: &str) -> Option<ClassSource> {
+        let vm = self.vm_key().ok()?;
+
+        read_class_cache(vm, |cache| {
+            let cached_class = cache?.classes.get(class_name)?;
+
+            Some(cached_class.source)
+        })
+    }
+
+    /// Sets the application class loader used to load classes that `FindClass` can't find.
+    ///
+    /// On threads attached from native code, `FindClass` resolves classes using the system class
+    /// loader, which doesn't know about the application classes. Setting the application class
+    /// loader, usually from `JNI_OnLoad`, allows those classes to be loaded on any thread.
+    pub fn set_class_loader(&self, class_loader: JObject) -> Result<(), JnixError> {
+        if class_loader.is_null() {
+            return Err(JnixError::invalid_value("Class loader reference is null")
+                .with_class("java/lang/ClassLoader"));
+        }
+
+        let class_loader = self
+            .env
+            .new_global_ref(class_loader)
+            .for_class(self, "java/lang/ClassLoader")?;
+
+        let previous_class_loader = write_class_cache(self.vm_key()?, |cache| {
+            cache.class_loader.replace(class_loader)
+        });
+
+        // Delete the previous global reference after the cache is unlocked
+        drop(previous_class_loader);
+
+        Ok(())
+    }
+
+    /// Sets the class loader that loaded the class of an object as the application class loader.
+    ///
+    /// See [`JnixEnv::set_class_loader`] for more information.
+    pub fn set_class_loader_of(&self, object: JObject) -> Result<(), JnixError> {
+        let class = self
+            .env
+            .get_object_class(object)
+            .for_class(self, "java/lang/Object")?;
+        let class_loader = self
+            .call_cached_method(
+                JObject::from(class),
+                "java/lang/Class",
+                "getClassLoader",
+                "()Ljava/lang/ClassLoader;",
+                JavaType::Object("java/lang/ClassLoader".to_owned()),
+                &[],
+            )?
+            .l()
+            .for_member(
+                self,
+                "java/lang/Class",
+                "getClassLoader",
+                "()Ljava/lang/ClassLoader;",
+            )?;
+
+        let _ = self.env.delete_local_ref(JObject::from(class));
+
+        self.set_class_loader(class_loader)
+    }
+
+    /// Sets the context class loader of the current thread as the application class loader.
+    ///
+    /// This is intended to be called from `JNI_OnLoad`, where the context class loader is usually
+    /// the application class loader. See [`JnixEnv::set_class_loader`] for more information.
+    pub fn capture_context_class_loader(&self) -> Result<(), JnixError> {
+        let thread = self
+            .call_cached_static_method(
+                "java/lang/Thread",
+                "currentThread",
+                "()Ljava/lang/Thread;",
+                JavaType::Object("java/lang/Thread".to_owned()),
+                &[],
+            )?
+            .l()
+            .for_member(
+                self,
+                "java/lang/Thread",
+                "currentThread",
+                "()Ljava/lang/Thread;",
+            )?;
+        let class_loader = self
+            .call_cached_method(
+                thread,
+                "java/lang/Thread",
+                "getContextClassLoader",
+                "()Ljava/lang/ClassLoader;",
+                JavaType::Object("java/lang/ClassLoader".to_owned()),
+                &[],
+            )?
+            .l()
+            .for_member(
+                self,
+                "java/lang/Thread",
+                "getContextClassLoader",
+                "()Ljava/lang/ClassLoader;",
+            )?;
+
+        let _ = self.env.delete_local_ref(thread);
+
+        self.set_class_loader(class_loader)
+    }
+
+    /// Returns the application class loader, if one was set.
+    pub fn class_loader(&self) -> Option<GlobalRef> {
+        let vm = self.vm_key().ok()?;
+
+        read_class_cache(vm, |cache| cache?.class_loader.clone())
+    }
+
+    /// Removes all classes from the class cache of the current Java VM, together with the cached
+    /// IDs of their members.
+    ///
+    /// The global references to the classes are deleted, so that the classes can be unloaded. The
+    /// application class loader is kept.
+    pub fn clear_class_cache(&self) {
+        let vm = match self.vm_key() {
+            Ok(vm) => vm,
+            Err(_) => return,
+        };
+
+        let classes = write_class_cache(vm, |cache| std::mem::take(&mut cache.classes));
+
+        // Clearing the IDs after the classes ensures that IDs resolved from the removed classes
+        // aren't stored afterwards.
+        clear_member_ids(vm);
+        drop(classes);
+    }
+
+    /// Removes all cached information about a Java VM that is being unloaded.
+    ///
+    /// This should be called from `JNI_OnUnload`, or before an embedded Java VM is destroyed. All
+    /// cached classes and member IDs of the Java VM are removed, and the global references to the
+    /// classes and to the application class loader are deleted.
+    pub fn on_unload(vm: &JavaVM) {
+        let vm_key = VmKey::from(vm);
+        let cache = remove_class_cache(vm_key);
+
+        clear_member_ids(vm_key);
+        drop(cache);
+    }
 
-        if let Some(class) = cache.get(class_name.as_ref()) {
-            class.clone()
+    /// Retrieves the ID of an instance method, using an internal cache.
+    ///
+    /// The class is obtained from the class cache, which keeps it loaded so that the cached ID
+    /// remains valid. The class name must be in the JNI format (e.g., `java/util/List`).
+    pub fn get_cached_method_id(
+        &self,
+        class_name: &str,
+        name: &str,
+        signature: &str,
+    ) -> Result<JMethodID<'env>, JnixError> {
+        get_or_resolve_member_id(
+            self.vm_key()?,
+            MemberKind::Method,
+            class_name,
+            name,
+            signature,
+            || {
+                let class = self.try_get_class(class_name)?;
+                let method_id = self.env.get_method_id(&class, name, signature).for_member(
+                    self,
+                    class_name.to_owned(),
+                    name.to_owned(),
+                    signature.to_owned(),
+                )?;
+
+                Ok(method_id.into_inner() as *mut c_void)
+            },
+        )
+        .map(|method_id| JMethodID::from(method_id as jmethodID))
+    }
+
+    /// Retrieves the ID of a static method, using an internal cache.
+    ///
+    /// See [`JnixEnv::get_cached_method_id`] for more information.
+    pub fn get_cached_static_method_id(
+        &self,
+        class_name: &str,
+        name: &str,
+        signature: &str,
+    ) -> Result<JStaticMethodID<'env>, JnixError> {
+        get_or_resolve_member_id(
+            self.vm_key()?,
+            MemberKind::StaticMethod,
+            class_name,
+            name,
+            signature,
+            || {
+                let class = self.try_get_class(class_name)?;
+                let method_id = self
+                    .env
+                    .get_static_method_id(&class, name, signature)
+                    .for_member(
+                        self,
+                        class_name.to_owned(),
+                        name.to_owned(),
+                        signature.to_owned(),
+                    )?;
+
+                Ok(method_id.into_inner() as *mut c_void)
+            },
+        )
+        .map(|method_id| JStaticMethodID::from(method_id as jmethodID))
+    }
+
+    /// Retrieves the ID of an instance field, using an internal cache.
+    ///
+    /// See [`JnixEnv::get_cached_method_id`] for more information.
+    pub fn get_cached_field_id(
+        &self,
+        class_name: &str,
+        name: &str,
+        signature: &str,
+    ) -> Result<JFieldID<'env>, JnixError> {
+        get_or_resolve_member_id(
+            self.vm_key()?,
+            MemberKind::Field,
+            class_name,
+            name,
+            signature,
+            || {
+                let class = self.try_get_class(class_name)?;
+                let field_id = self.env.get_field_id(&class, name, signature).for_member(
+                    self,
+                    class_name.to_owned(),
+                    name.to_owned(),
+                    signature.to_owned(),
+                )?;
+
+                Ok(field_id.into_inner() as *mut c_void)
+            },
+        )
+        .map(|field_id| JFieldID::from(field_id as jfieldID))
+    }
+
+    /// Retrieves the ID of a static field, using an internal cache.
+    ///
+    /// See [`JnixEnv::get_cached_method_id`] for more information.
+    pub fn get_cached_static_field_id(
+        &self,
+        class_name: &str,
+        name: &str,
+        signature: &str,
+    ) -> Result<JStaticFieldID<'env>, JnixError> {
+        get_or_resolve_member_id(
+            self.vm_key()?,
+            MemberKind::StaticField,
+            class_name,
+            name,
+            signature,
+            || {
+                let class = self.try_get_class(class_name)?;
+                let field_id = self
+                    .env
+                    .get_static_field_id(&class, name, signature)
+                    .for_member(
+                        self,
+                        class_name.to_owned(),
+                        name.to_owned(),
+                        signature.to_owned(),
+                    )?;
+
+                Ok(field_id.into_inner() as *mut c_void)
+            },
+        )
+        .map(|field_id| JStaticFieldID::from(field_id as jfieldID))
+    }
+
+    /// Calls an instance method using its cached method ID.
+    pub(crate) fn call_cached_method<'object>(
+        &self,
+        object: JObject<'object>,
+        class_name: &'static str,
+        name: &'static str,
+        signature: &'static str,
+        return_type: JavaType,
+        arguments: &[JValue],
+    ) -> Result<JValue<'object>, JnixError>
+    where
+        'env: 'object,
+    {
+        let method_id = self.get_cached_method_id(class_name, name, signature)?;
+        let env: &JNIEnv<'object> = &self.env;
+
+        env.call_method_unchecked(object, method_id, return_type, arguments)
+            .for_member(self, class_name, name, signature)
+    }
+
+    /// Calls a static method using its cached method ID.
+    pub(crate) fn call_cached_static_method(
+        &self,
+        class_name: &'static str,
+        name: &'static str,
+        signature: &'static str,
+        return_type: JavaType,
+        arguments: &[JValue],
+    ) -> Result<JValue<'env>, JnixError> {
+        let class = self.try_get_class(class_name)?;
+        let method_id = self.get_cached_static_method_id(class_name, name, signature)?;
+
+        self.env
+            .call_static_method_unchecked(&class, method_id, return_type, arguments)
+            .for_member(self, class_name, name, signature)
+    }
+
+    /// Creates a new object using the cached method ID of its constructor.
+    pub(crate) fn new_cached_object(
+        &self,
+        class_name: &'static str,
+        signature: &'static str,
+        arguments: &[JValue],
+    ) -> Result<JObject<'env>, JnixError> {
+        let class = self.try_get_class(class_name)?;
+        let constructor_id = self.get_cached_method_id(class_name, "<init>", signature)?;
+
+        self.env
+            .new_object_unchecked(&class, constructor_id, arguments)
+            .for_member(self, class_name, "<init>", signature)
+    }
+
+    /// Takes the pending Java exception, if there is one.
+    ///
+    /// The exception is cleared from the JNI environment, so that later JNI calls can be made
+    /// safely, and its information is captured into a [`JavaException`].
+    pub fn take_exception(&self) -> Option<JavaException> {
+        if !self.env.exception_check().unwrap_or(false) {
+            return None;
+        }
+
+        let throwable = self.env.exception_occurred();
+        let _ = self.env.exception_clear();
+        let throwable = JObject::from(throwable.ok()?);
+
+        if throwable.is_null() {
+            return None;
+        }
+
+        let exception = JavaException::from_throwable(self, throwable);
+        let _ = self.env.delete_local_ref(throwable);
+
+        Some(exception)
+    }
+
+    /// Converts an error from a low-level JNI operation into a [`JnixError`].
+    ///
+    /// If there is a pending Java exception, it is taken from the JNI environment and used as the
+    /// cause of the error. Otherwise, the JNI error is used as the cause.
+    pub fn convert_jni_error(&self, error: jni::errors::Error) -> JnixError {
+        match self.take_exception() {
+            Some(exception) => JnixError::new(JnixErrorKind::JavaException(exception)),
+            None => JnixError::from(error),
+        }
+    }
+
+    /// Creates a Java string from a Rust string.
+    ///
+    /// The string is passed to the JVM as UTF-16, so that all characters, including characters
+    /// outside the Basic Multilingual Plane and NUL characters, are preserved. ASCII strings without
+    /// NUL characters are passed directly, because they have the same representation in JNI's
+    /// modified UTF-8.
+    pub fn new_utf16_string(&self, string: &str) -> Result<JString<'env>, JnixError> {
+        if string.is_ascii() && !string.contains('\0') {
+            return self
+                .env
+                .new_string(string)
+                .for_class(self, "java/lang/String");
+        }
+
+        let code_units: Vec<u16> = string.encode_utf16().collect();
+        let raw_env = self.env.get_native_interface();
+
+        let java_string = unsafe {
+            let new_string = (**raw_env)
+                .NewString
+                .ok_or(jni::errors::Error::JNIEnvMethodNotFound("NewString"))
+                .for_class(self, "java/lang/String")?;
+
+            new_string(raw_env, code_units.as_ptr(), code_units.len() as jsize)
+        };
+
+        if java_string.is_null() {
+            Err(self
+                .convert_jni_error(jni::errors::Error::NullPtr("NewString result"))
+                .with_class("java/lang/String"))
         } else {
-            let class = self.load_class(class_name.as_ref());
+            Ok(JString::from(java_string))
+        }
+    }
 
-            cache.insert(class_name.into_owned(), class.clone());
+    /// Reads a Java string into a Rust string.
+    ///
+    /// The string is read from the JVM as UTF-16, so that all characters, including characters
+    /// outside the Basic Multilingual Plane and NUL characters, are preserved. Lone surrogates,
+    /// which can't be represented in a Rust string, are handled as specified.
+    pub fn get_utf16_string(
+        &self,
+        java_string: JString,
+        lone_surrogates: LoneSurrogates,
+    ) -> Result<String, JnixError> {
+        if java_string.is_null() {
+            return Err(
+                JnixError::new(jni::errors::Error::NullPtr("String reference"))
+                    .with_class("java/lang/String"),
+            );
+        }
+
+        let raw_env = self.env.get_native_interface();
+        let raw_string = java_string.into_inner();
+
+        let code_units = unsafe {
+            let get_string_length = (**raw_env)
+                .GetStringLength
+                .ok_or(jni::errors::Error::JNIEnvMethodNotFound("GetStringLength"))
+                .for_class(self, "java/lang/String")?;
+            let get_string_region = (**raw_env)
+                .GetStringRegion
+                .ok_or(jni::errors::Error::JNIEnvMethodNotFound("GetStringRegion"))
+                .for_class(self, "java/lang/String")?;
+
+            let length = get_string_length(raw_env, raw_string);
+            let mut code_units = Vec::<u16>::with_capacity(length as usize);
+
+            get_string_region(raw_env, raw_string, 0, length, code_units.as_mut_ptr());
+
+            if self.env.exception_check().unwrap_or(false) {
+                return Err(self
+                    .convert_jni_error(jni::errors::Error::JavaException)
+                    .with_class("java/lang/String"));
+            }
+
+            code_units.set_len(length as usize);
+            code_units
+        };
+
+        match lone_surrogates {
+            LoneSurrogates::Replace => Ok(String::from_utf16_lossy(&code_units)),
+            LoneSurrogates::Fail => String::from_utf16(&code_units).map_err(|_| {
+                JnixError::invalid_value("Java string contains a lone surrogate")
+                    .with_class("java/lang/String")
+            }),
+        }
+    }
+
+    /// Throws a Rust error as a Java exception.
+    ///
+    /// A Java exception that is still pending is used as the cause of the thrown exception. If the
+    /// exception can't be created or thrown, a `java.lang.RuntimeException` describing the failure
+    /// is thrown instead.
+    pub fn throw_exception(&self, error: &impl IntoJavaException) {
+        if let Err(throw_error) = error.throw(self) {
+            // Creating the exception may have failed with another pending exception.
+            let _ = self.take_exception();
 
-            class
+            let message = format!(
+                "Failed to throw {} ({}): {}",
+                error.exception_class_name(),
+                error.exception_message(),
+                throw_error,
+            );
+
+            let _ = self.env.throw_new("java/lang/RuntimeException", message);
         }
     }
 
-    /// Loads a class and stores it in the class cache.
+    /// Returns the value of a successful result, or throws the error as a Java exception.
+    ///
+    /// This is useful for native methods that don't return a value.
+    pub fn unwrap_or_throw<T, E>(&self, result: Result<T, E>) -> Option<T>
+    where
+        E: IntoJavaException,
+    {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.throw_exception(&error);
+                None
+            }
+        }
+    }
+
+    /// Converts the result of a native method into the raw value to return to Java.
+    ///
+    /// A successful value is converted into its Java type. An error is thrown as a Java
+    /// exception, and a default value (`null` or zero) is returned instead.
+    pub fn into_java_or_throw<'borrow, T, E>(
+        &'borrow self,
+        result: Result<T, E>,
+    ) -> <T::JavaType as JniReturnValue>::JniType
+    where
+        T: IntoJava<'borrow, 'env>,
+        T::JavaType: JniReturnValue,
+        E: IntoJavaException,
+    {
+        match self.unwrap_or_throw(result) {
+            Some(value) => value.into_java(self).into_jni_return_value(),
+            None => T::JavaType::default_jni_return_value(),
+        }
+    }
+
+    /// Runs the body of a native method, converting any panic into a thrown Java exception.
+    ///
+    /// Unwinding a panic across an `extern "system"` function is undefined behavior, so native
+    /// methods should use this to wrap their body. If the closure panics, a
+    /// `java.lang.RuntimeException` is thrown with the panic message, and a default value (`null`
+    /// or zero) is returned instead.
+    ///
+    /// The panic location is only included in the message if the panic hook installed by
+    /// [`JnixEnv::install_panic_hook`] is active, since this doesn't change the process-wide panic
+    /// hook by itself. The hook is also installed by [`JnixEnv::register_natives`], so it is
+    /// active for native methods exported with `#[jnix::export]` and bound that way. If the
+    /// methods are bound through their symbol names instead, the hook should be installed from
+    /// `JNI_OnLoad`.
+    ///
+    /// The closure is assumed to be unwind safe, since the environment is not used after a panic
+    /// except to throw the exception.
+    pub fn catch_panic<'borrow, T>(
+        &'borrow self,
+        function: impl FnOnce(&'borrow Self) -> T,
+    ) -> T::JniType
+    where
+        T: JniReturnValue,
+    {
+        self.catch_panic_as("java/lang/RuntimeException", function)
+    }
+
+    /// Runs the body of a native method, converting any panic into a thrown Java exception of the
+    /// specified class.
+    ///
+    /// Works like [`JnixEnv::catch_panic`], but throws an exception of the specified class, which
+    /// must have a constructor that receives the message as a `String`. The class name must be in
+    /// the JNI format (e.g., `java/lang/IllegalStateException`).
+    pub fn catch_panic_as<'borrow, T>(
+        &'borrow self,
+        exception_class: &str,
+        function: impl FnOnce(&'borrow Self) -> T,
+    ) -> T::JniType
+    where
+        T: JniReturnValue,
+    {
+        clear_panic_location();
+
+        match panic::catch_unwind(AssertUnwindSafe(|| function(self))) {
+            Ok(value) => value.into_jni_return_value(),
+            Err(payload) => {
+                let pending_exception = self.take_exception();
+
+                self.throw_exception(&PanicException::new(
+                    exception_class,
+                    payload,
+                    pending_exception,
+                ));
+
+                T::default_jni_return_value()
+            }
+        }
+    }
+
+    /// Installs a process-wide panic hook that records the location of panics, so that
+    /// [`JnixEnv::catch_panic`] and [`JnixEnv::catch_panic_as`] can include it in the thrown
+    /// exception.
+    ///
+    /// This is usually called once from `JNI_OnLoad`, and calling it again has no effect. It is
+    /// called by [`JnixEnv::register_natives`] and [`JnixEnv::register_natives_from`], so it only
+    /// needs to be called explicitly if native methods aren't bound with them. The
+    /// previously installed hook is still called after the location is recorded. A hook installed
+    /// later replaces this one, so the location is only recorded if that hook calls the hook it
+    /// replaced.
+    pub fn install_panic_hook() {
+        install_panic_location_hook();
+    }
+
+    /// Runs a closure inside a new local reference frame, keeping only the object it returns.
+    ///
+    /// The frame has space for at least `capacity` local references, and all local references
+    /// created while the closure runs are deleted when it returns, even if it returns an error or
+    /// panics. This is useful to limit the number of live local references in loops that convert
+    /// many objects.
+    ///
+    /// The object returned by the closure is moved into a new local reference in the enclosing
+    /// frame, which is returned instead. The closure can return a null object if there's no result.
+    /// Other values that must outlive the frame can be returned as a [`GlobalRef`], or created
+    /// outside of the frame.
+    pub fn with_local_frame<'borrow>(
+        &'borrow self,
+        capacity: i32,
+        function: impl FnOnce(&'borrow Self) -> Result<JObject<'env>, JnixError>,
+    ) -> Result<JObject<'env>, JnixError> {
+        self.env
+            .push_local_frame(capacity)
+            .for_class(self, "java/lang/Object")?;
+
+        let frame = LocalFrame { env: &self.env };
+        let result = function(self)?;
+
+        mem::forget(frame);
+
+        self.env
+            .pop_local_frame(result)
+            .for_class(self, "java/lang/Object")
+    }
+
+    /// Binds all native methods exported with the `#[jnix::export]` attribute macro to their Java
+    /// classes using `RegisterNatives`.
+    ///
+    /// This is usually called from `JNI_OnLoad`. Binding methods explicitly avoids relying on the
+    /// JVM to resolve the exported symbol names, which can break if the Java classes are renamed
+    /// (for example, by code shrinkers) and can be slow for large libraries.
+    ///
+    /// The panic hook of [`JnixEnv::install_panic_hook`] is also installed, so that the exceptions
+    /// thrown for panics in the bound methods include the location of the panic.
+    ///
+    /// All methods are attempted, and the ones that failed to bind are listed in the returned
+    /// error.
+    pub fn register_natives(&self) -> Result<(), RegisterNativesError> {
+        self.register_natives_from(registered_native_methods())
+    }
+
+    /// Binds the specified native methods to their Java classes using `RegisterNatives`.
+    ///
+    /// Like [`JnixEnv::register_natives`], this also installs the panic hook. All methods are
+    /// attempted, and the ones that failed to bind are listed in the returned error.
+    pub fn register_natives_from<'a>(
+        &self,
+        methods: impl IntoIterator<Item = &'a NativeMethod>,
+    ) -> Result<(), RegisterNativesError> {
+        install_panic_location_hook();
+
+        let failures: Vec<_> = methods
+            .into_iter()
+            .filter_map(|method| method.register(self).err())
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(RegisterNativesError::new(failures))
+        }
+    }
+
+    /// Loads classes and stores them in the class cache.
+    ///
+    /// Classes that are already in the cache aren't loaded again.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if a class can't be loaded. All classes are attempted first, so that
+    /// the panic message lists all classes that failed to load.
     pub fn preload_classes(&self, class_names: impl IntoIterator<Item = impl Into<String>>) {
-        let mut cache = CLASS_CACHE.lock();
+        let report = self.try_preload_classes(class_names);
+
+        if !report.is_complete() {
+            panic!("{}", report);
+        }
+    }
+
+    /// Loads classes and stores them in the class cache, reporting which classes failed to load.
+    ///
+    /// Classes that are already in the cache aren't loaded again. All classes are attempted, even
+    /// if some of them fail to load.
+    pub fn try_preload_classes(
+        &self,
+        class_names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> PreloadReport {
+        let mut report = PreloadReport::default();
 
         for class_name in class_names {
             let class_name = class_name.into();
-            let class = self.load_class(&class_name);
 
-            cache.insert(class_name, class);
+            match self.try_get_class(class_name.as_str()) {
+                Ok(_) => report.add_loaded(class_name),
+                Err(error) => report.add_failure(class_name, error),
+            }
         }
+
+        report
+    }
+
+    /// Loads all classes used by the types that derive the conversion traits, and stores them in
+    /// the class cache.
+    ///
+    /// This includes the classes of structs and enums, and the classes of the variants of enums
+    /// that are mapped to sealed classes (e.g., `my/package/MyEnum$Variant`). It should be called
+    /// from a thread where the application classes can be found, usually from `JNI_OnLoad`.
+    ///
+    /// All classes are attempted, and the returned report lists the ones that failed to load.
+    pub fn preload_all_derived_classes(&self) -> PreloadReport {
+        self.try_preload_classes(derived_class_names())
+    }
+
+    /// Returns the names of the classes in the class cache of the current Java VM, sorted by
+    /// name, together with how they were loaded.
+    pub fn cached_classes(&self) -> Vec<(String, ClassSource)> {
+        let vm = match self.vm_key() {
+            Ok(vm) => vm,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut classes: Vec<_> = read_class_cache(vm, |cache| {
+            cache
+                .into_iter()
+                .flat_map(|cache| &cache.classes)
+                .map(|(class_name, cached_class)| (class_name.clone(), cached_class.source))
+                .collect()
+        });
+
+        classes.sort_by(|(first, _), (second, _)| first.cmp(second));
+        classes
+    }
+
+    /// Loads a class that isn't in the class cache and stores it in the cache.
+    ///
+    /// The class cache isn't locked while the class is loaded, because loading it may run Java
+    /// code that uses the cache again. If another thread stores the same class in the meantime,
+    /// the class stored by the other thread is used.
+    fn load_and_cache_class(
+        &self,
+        vm: VmKey,
+        class_name: Cow<'_, str>,
+    ) -> Result<GlobalRef, JnixError> {
+        let class_loader = read_class_cache(vm, |cache| cache?.class_loader.clone());
+        let loaded_class = self.load_class(class_name.as_ref(), class_loader.as_ref())?;
+
+        let (class, unused_class) = write_class_cache(vm, |cache| {
+            match cache.classes.entry(class_name.into_owned()) {
+                Entry::Occupied(entry) => (entry.get().class.clone(), Some(loaded_class)),
+                Entry::Vacant(entry) => (entry.insert(loaded_class).class.clone(), None),
+            }
+        });
+
+        // Delete the global reference to the duplicate class after the cache is unlocked
+        drop(unused_class);
+
+        Ok(class)
+    }
+
+    /// Returns the key of the Java VM that owns this environment, used to select its caches.
+    fn vm_key(&self) -> Result<VmKey, JnixError> {
+        self.vm
+            .get_or_try_init(|| {
+                let vm = self.env.get_java_vm().map_err(JnixError::from)?;
+
+                Ok(VmKey::from(&vm))
+            })
+            .copied()
     }
 
-    fn load_class(&self, class_name: impl AsRef<str>) -> GlobalRef {
-        let class_name = class_name.as_ref();
-        let local_ref = self
+    /// Loads a class with `FindClass`, falling back to the application class loader if there is
+    /// one.
+    fn load_class(
+        &self,
+        class_name: &str,
+        class_loader: Option<&GlobalRef>,
+    ) -> Result<CachedClass, JnixError> {
+        let (local_ref, source) = match self.env.find_class(class_name) {
+            Ok(class) => (JObject::from(class), ClassSource::FindClass),
+            Err(error) => {
+                let find_class_error = self
+                    .convert_jni_error(error)
+                    .with_class(class_name.to_owned());
+
+                let class_loader = match class_loader {
+                    Some(class_loader) => class_loader,
+                    None => return Err(find_class_error),
+                };
+
+                // Report the `NoClassDefFoundError` from `FindClass` if the class loader also
+                // fails, since it describes the missing class best
+                match self.load_class_with(class_loader, class_name) {
+                    Ok(class) => (class, ClassSource::ClassLoader),
+                    Err(_) => {
+                        return Err(find_class_error.with_context(format!(
+                            "Loading {} with FindClass and with the application class loader",
+                            class_name
+                        )))
+                    }
+                }
+            }
+        };
+
+        let class = self
             .env
-            .find_class(class_name)
-            .expect(&format!("Failed to find {} Java class", class_name));
+            .new_global_ref(local_ref)
+            .for_class(self, class_name.to_owned())?;
 
-        self.env.new_global_ref(JObject::from(local_ref)).expect(
-            "Failed to convert local reference to Java class object into a global reference",
-        )
+        let _ = self.env.delete_local_ref(local_ref);
+
+        Ok(CachedClass { class, source })
+    }
+
+    /// Loads a class with `ClassLoader.loadClass`, which expects the binary name of the class
+    /// (e.g., `my.package.MyClass$Inner`).
+    fn load_class_with(
+        &self,
+        class_loader: &GlobalRef,
+        class_name: &str,
+    ) -> Result<JObject<'env>, JnixError> {
+        let binary_name = self
+            .env
+            .new_string(class_name.replace('/', "."))
+            .for_class(self, class_name.to_owned())?;
+        let class_loader = JObject::from(class_loader.as_obj().into_inner());
+
+        let class = self
+            .env
+            .call_method(
+                class_loader,
+                "loadClass",
+                "(Ljava/lang/String;)Ljava/lang/Class;",
+                &[JValue::Object(binary_name.into())],
+            )
+            .and_then(JValue::l)
+            .for_member(
+                self,
+                "java/lang/ClassLoader",
+                "loadClass",
+                "(Ljava/lang/String;)Ljava/lang/Class;",
+            );
+
+        let _ = self.env.delete_local_ref(binary_name.into());
+
+        class
+    }
+}
+
+/// Removes the innermost local reference frame when dropped, deleting all local references in it.
+///
+/// Used when the closure of [`JnixEnv::with_local_frame`] fails or panics. It's forgotten when the
+/// frame is removed keeping the result object.
+struct LocalFrame<'a, 'env> {
+    env: &'a JNIEnv<'env>,
+}
+
+impl Drop for LocalFrame<'_, '_> {
+    fn drop(&mut self) {
+        let _ = self.env.pop_local_frame(JObject::null());
     }
 }
@@ -0,0 +1,95 @@
+use crate::{JnixEnv, JnixError};
+use jni::{JNIEnv, JavaVM};
+use std::sync::Arc;
+
+/// Provides access to a Java VM from any thread.
+///
+/// The [`JnixEnv`]s given out share the class cache and the application class loader of the Java
+/// VM, so classes loaded on one thread can be used on all threads.
+#[derive(Clone)]
+pub struct JnixVm {
+    vm: Arc<JavaVM>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AttachMode {
+    Scoped,
+    Permanent,
+    Daemon,
+}
+
+impl From<JavaVM> for JnixVm {
+    fn from(vm: JavaVM) -> Self {
+        JnixVm { vm: Arc::new(vm) }
+    }
+}
+
+impl JnixVm {
+    /// Creates a wrapper for the Java VM that owns a JNI environment.
+    pub fn from_env(env: &JNIEnv) -> Result<Self, JnixError> {
+        Ok(JnixVm::from(env.get_java_vm()?))
+    }
+
+    /// Returns the wrapped Java VM.
+    pub fn java_vm(&self) -> &JavaVM {
+        &self.vm
+    }
+
+    /// Runs a closure with a [`JnixEnv`] for the current thread.
+    ///
+    /// If the thread is already attached to the Java VM, its environment is reused. Otherwise, the
+    /// thread is attached while the closure runs and detached afterwards.
+    pub fn with_env<T>(&self, function: impl FnOnce(&JnixEnv) -> T) -> Result<T, JnixError> {
+        self.with_env_attached(AttachMode::Scoped, function)
+    }
+
+    /// Runs a closure with a [`JnixEnv`] for the current thread, attaching the thread permanently
+    /// if it isn't attached yet.
+    ///
+    /// The thread stays attached until it exits, when it is detached automatically. This avoids
+    /// the cost of attaching the thread on every call, but attached threads prevent the Java VM
+    /// from exiting.
+    pub fn with_permanent_env<T>(
+        &self,
+        function: impl FnOnce(&JnixEnv) -> T,
+    ) -> Result<T, JnixError> {
+        self.with_env_attached(AttachMode::Permanent, function)
+    }
+
+    /// Runs a closure with a [`JnixEnv`] for the current thread, attaching the thread permanently
+    /// as a daemon thread if it isn't attached yet.
+    ///
+    /// Works like [`JnixVm::with_permanent_env`], but daemon threads don't prevent the Java VM
+    /// from exiting.
+    pub fn with_daemon_env<T>(&self, function: impl FnOnce(&JnixEnv) -> T) -> Result<T, JnixError> {
+        self.with_env_attached(AttachMode::Daemon, function)
+    }
+
+    fn with_env_attached<T>(
+        &self,
+        mode: AttachMode,
+        function: impl FnOnce(&JnixEnv) -> T,
+    ) -> Result<T, JnixError> {
+        if let Ok(env) = self.vm.get_env() {
+            return Ok(function(&JnixEnv::from(env)));
+        }
+
+        match mode {
+            AttachMode::Scoped => {
+                let guard = self.vm.attach_current_thread()?;
+
+                Ok(function(&JnixEnv::from(*guard)))
+            }
+            AttachMode::Permanent => {
+                let env = self.vm.attach_current_thread_permanently()?;
+
+                Ok(function(&JnixEnv::from(env)))
+            }
+            AttachMode::Daemon => {
+                let env = self.vm.attach_current_thread_as_daemon()?;
+
+                Ok(function(&JnixEnv::from(env)))
+            }
+        }
+    }
+}
@@ -6,13 +6,36 @@
 //! - [`AsJValue`]: for allowing a JNI type to be convected to a `JValue` wrapper type.
 //! - [`IntoJava`]: for allowing a Rust type to be converted to a Java type.
 //! - [`FromJava`]: for allowing a Rust type to be created from a Java type.
+//! - [`TryIntoJava`] and [`TryFromJava`]: fallible versions of the conversion traits, which
+//!   return a [`JnixError`] instead of panicking if the conversion fails. If the failure was caused
+//!   by a Java exception, the exception is cleared and captured into a [`JavaException`].
+//! - [`IntoJavaException`]: for allowing a Rust error to be thrown as a Java exception. The
+//!   [`JnixEnv`] helper methods `unwrap_or_throw` and `into_java_or_throw` use it to convert the
+//!   `Result` of a native method into either its return value or a thrown exception.
 //!
 //! A [`JnixEnv`] helper type is also provided, which is a [`JNIEnv`] wrapper that contains an
-//! internal class cache for preloaded classes.
+//! internal class cache for preloaded classes. It can also run the body of a native method with
+//! `catch_panic`, so that panics are thrown as Java exceptions instead of unwinding into the JVM.
+//! Classes that `FindClass` can't find on threads attached from native code are loaded with an
+//! application class loader, which can be set with `set_class_loader` from `JNI_OnLoad`. The
+//! classes used by derived conversions can also be loaded there with `preload_all_derived_classes`.
+//!
+//! The exceptions thrown for panics only include the location of the panic after a panic hook is
+//! installed with `JnixEnv::install_panic_hook`, usually from `JNI_OnLoad`. Binding the native
+//! methods with `register_natives` installs it automatically, but native methods that the JVM binds
+//! through their symbol names need the explicit call.
+//!
+//! A [`JnixVm`] type wraps the Java VM and gives out `JnixEnv`s on any thread, attaching the
+//! thread to the Java VM if necessary.
+//!
+//! If compiled with the `indexmap` feature flag, `IndexMap` can also be converted to and from Java
+//! maps, keeping its insertion order through a `java.util.LinkedHashMap`.
 //!
 //! If compiled with the `derive` feature flag, the crate also exports procedural macros to
 //! [derive `IntoJava`] and to [derive `FromJava`], which makes writing conversion code a lot
-//! easier.  An example would be:
+//! easier. The `TryIntoJava` and `TryFromJava` traits can be derived in the same way. The
+//! [`export`] attribute macro can also generate native method entry points, like the one in the
+//! example below, from plain Rust functions. An example would be:
 //!
 //! ```rust
 //! use jnix::{
@@ -79,19 +102,69 @@
 //! [`AsJValue`]: as_jvalue::AsJValue
 //! [`IntoJava`]: into_java::IntoJava
 //! [`FromJava`]: from_java::FromJava
+//! [`TryIntoJava`]: into_java::TryIntoJava
+//! [`TryFromJava`]: from_java::TryFromJava
+//! [`JnixError`]: error::JnixError
+//! [`JavaException`]: java_exception::JavaException
+//! [`IntoJavaException`]: into_java_exception::IntoJavaException
 //! [`JnixEnv`]: jnix_env::JnixEnv
+//! [`JnixVm`]: jnix_vm::JnixVm
 //! [derive `IntoJava`]: ../jnix_macros/derive.IntoJava.html
 //! [derive `FromJava`]: ../jnix_macros/derive.FromJava.html
+//! [`export`]: ../jnix_macros/attr.export.html
 
 #![deny(missing_docs)]
 
 pub extern crate jni;
 
+#[doc(hidden)]
+pub use inventory;
+
+#[doc(hidden)]
+pub mod call_type;
+#[doc(hidden)]
+pub mod const_concat;
+
+mod array_signature;
 mod as_jvalue;
+mod boxing;
+mod character;
+mod class_cache;
+mod collection;
+mod derived_classes;
+mod error;
 mod from_java;
 mod into_java;
+mod into_java_exception;
+mod java_exception;
+mod jni_argument;
+mod jni_return_value;
 mod jnix_env;
+mod jnix_vm;
+mod member_id_cache;
+mod native_methods;
+mod panic;
+mod unsigned;
+mod utf16;
 
-pub use self::{as_jvalue::AsJValue, from_java::FromJava, into_java::IntoJava, jnix_env::JnixEnv};
+pub use self::{
+    as_jvalue::AsJValue,
+    character::{CharString, CodePoint},
+    class_cache::{ClassSource, PreloadReport},
+    collection::{JavaArray, JavaList},
+    derived_classes::DerivedClass,
+    error::{JnixError, JnixErrorKind},
+    from_java::{FromJava, TryFromJava},
+    into_java::{IntoJava, TryIntoJava},
+    into_java_exception::IntoJavaException,
+    java_exception::{JavaException, StackTraceElement},
+    jni_argument::JniArgument,
+    jni_return_value::JniReturnValue,
+    jnix_env::JnixEnv,
+    jnix_vm::JnixVm,
+    native_methods::{NativeMethod, RegisterNativesError},
+    unsigned::{Checked, Widened},
+    utf16::{LoneSurrogates, StrictString},
+};
 #[cfg(feature = "derive")]
-pub use jnix_macros::{FromJava, IntoJava};
+pub use jnix_macros::{export, FromJava, IntoJava, IntoJavaException, TryFromJava, TryIntoJava};
@@ -0,0 +1,200 @@
+use crate::{class_cache::VmKey, JnixError};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    ffi::c_void,
+    hash::{Hash, Hasher},
+};
+
+static MEMBER_ID_CACHE: Lazy<RwLock<MemberIdCache>> =
+    Lazy::new(|| RwLock::new(MemberIdCache::default()));
+
+#[derive(Default)]
+struct MemberIdCache {
+    ids: HashMap<MemberKey, MemberId>,
+    /// The number of times that the IDs of each Java VM were cleared.
+    ///
+    /// An ID is only stored if no clear happened while it was being resolved, because it may
+    /// belong to a class that was removed from the class cache.
+    generations: HashMap<VmKey, u64>,
+}
+
+impl MemberIdCache {
+    fn generation(&self, vm: VmKey) -> u64 {
+        self.generations.get(&vm).copied().unwrap_or(0)
+    }
+}
+
+/// The kind of a class member whose ID is cached.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum MemberKind {
+    Method,
+    StaticMethod,
+    Field,
+    StaticField,
+}
+
+/// A raw method ID or field ID.
+///
+/// The IDs remain valid while the class is loaded, which is guaranteed by the global reference kept
+/// in the class cache.
+#[derive(Clone, Copy)]
+struct MemberId(*mut c_void);
+
+// The IDs are opaque handles that can be used from any thread.
+unsafe impl Send for MemberId {}
+unsafe impl Sync for MemberId {}
+
+struct MemberKey {
+    vm: VmKey,
+    kind: MemberKind,
+    class_name: String,
+    name: String,
+    signature: String,
+}
+
+/// A view of a cache key, so that entries can be looked up without allocating an owned key.
+trait AsMemberKey {
+    fn parts(&self) -> (VmKey, MemberKind, &str, &str, &str);
+}
+
+impl AsMemberKey for MemberKey {
+    fn parts(&self) -> (VmKey, MemberKind, &str, &str, &str) {
+        (
+            self.vm,
+            self.kind,
+            &self.class_name,
+            &self.name,
+            &self.signature,
+        )
+    }
+}
+
+impl AsMemberKey for (VmKey, MemberKind, &str, &str, &str) {
+    fn parts(&self) -> (VmKey, MemberKind, &str, &str, &str) {
+        *self
+    }
+}
+
+impl<'a> Borrow<dyn AsMemberKey + 'a> for MemberKey {
+    fn borrow(&self) -> &(dyn AsMemberKey + 'a) {
+        self
+    }
+}
+
+impl Hash for dyn AsMemberKey + '_ {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.parts().hash(hasher)
+    }
+}
+
+impl PartialEq for dyn AsMemberKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for dyn AsMemberKey + '_ {}
+
+impl Hash for MemberKey {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        self.parts().hash(hasher)
+    }
+}
+
+impl PartialEq for MemberKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts() == other.parts()
+    }
+}
+
+impl Eq for MemberKey {}
+
+/// Retrieves a member ID from the cache, resolving it and storing it in the cache if it's not
+/// present.
+///
+/// The cache isn't locked while the ID is resolved, so that resolving it can use the class cache.
+/// If the IDs of the Java VM are cleared in the meantime, the resolved ID is returned but not
+/// stored, because the class it was resolved from may be unloaded after the clear.
+pub(crate) fn get_or_resolve_member_id(
+    vm: VmKey,
+    kind: MemberKind,
+    class_name: &str,
+    name: &str,
+    signature: &str,
+    resolve: impl FnOnce() -> Result<*mut c_void, JnixError>,
+) -> Result<*mut c_void, JnixError> {
+    let key = (vm, kind, class_name, name, signature);
+
+    let generation = {
+        let cache = MEMBER_ID_CACHE.read();
+
+        if let Some(id) = cache.ids.get(&key as &dyn AsMemberKey) {
+            return Ok(id.0);
+        }
+
+        cache.generation(vm)
+    };
+
+    let id = resolve()?;
+    let mut cache = MEMBER_ID_CACHE.write();
+
+    if cache.generation(vm) == generation {
+        cache.ids.insert(
+            MemberKey {
+                vm,
+                kind,
+                class_name: class_name.to_owned(),
+                name: name.to_owned(),
+                signature: signature.to_owned(),
+            },
+            MemberId(id),
+        );
+    }
+
+    Ok(id)
+}
+
+/// Removes the cached IDs of the members of the classes loaded by a Java VM.
+///
+/// This must be done when the classes are removed from the class cache, because the IDs may become
+/// invalid once the classes are unloaded. IDs that are being resolved while this runs won't be
+/// stored.
+pub(crate) fn clear_member_ids(vm: VmKey) {
+    let mut cache = MEMBER_ID_CACHE.write();
+
+    cache.ids.retain(|key, _| key.vm != vm);
+    *cache.generations.entry(vm).or_insert(0) += 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr::NonNull;
+
+    fn resolve_method_id(
+        vm: VmKey,
+        resolve: impl FnOnce() -> Result<*mut c_void, JnixError>,
+    ) -> *mut c_void {
+        get_or_resolve_member_id(vm, MemberKind::Method, "a/B", "c", "()V", resolve).unwrap()
+    }
+
+    #[test]
+    fn id_resolved_during_a_clear_is_not_stored() {
+        let vm = VmKey::new(NonNull::dangling().as_ptr());
+        let mut ids = [0_u8; 2];
+        let stale_id = &mut ids[0] as *mut u8 as *mut c_void;
+        let fresh_id = &mut ids[1] as *mut u8 as *mut c_void;
+
+        let id = resolve_method_id(vm, || {
+            clear_member_ids(vm);
+            Ok(stale_id)
+        });
+
+        assert_eq!(id, stale_id);
+        assert_eq!(resolve_method_id(vm, || Ok(fresh_id)), fresh_id);
+        assert_eq!(resolve_method_id(vm, || panic!("ID not cached")), fresh_id);
+    }
+}
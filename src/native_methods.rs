@@ -0,0 +1,133 @@
+use crate::{error::JniResultExt, JnixEnv, JnixError};
+use std::{
+    error::Error,
+    ffi::c_void,
+    fmt::{self, Display, Formatter},
+};
+
+/// A native method implemented in Rust that can be bound to its Java class using
+/// `RegisterNatives`.
+///
+/// Native methods exported with the `#[jnix::export]` attribute macro are automatically added to
+/// a global registry, and can be bound with [`JnixEnv::register_natives`].
+pub struct NativeMethod {
+    class_name: &'static str,
+    name: &'static str,
+    parameter_signatures: &'static [&'static str],
+    return_signature: &'static str,
+    function: *mut c_void,
+}
+
+// The function pointer is never dereferenced, it's only handed over to the JVM.
+unsafe impl Send for NativeMethod {}
+unsafe impl Sync for NativeMethod {}
+
+inventory::collect!(NativeMethod);
+
+impl NativeMethod {
+    /// Creates a description of a native method.
+    ///
+    /// The class name must be in the JNI format (e.g., `my/package/MyClass`). The signatures are
+    /// the JNI signatures of the parameter types and of the return type.
+    ///
+    /// # Safety
+    ///
+    /// The function must be an `extern "system"` function that receives a `JNIEnv`, the `this`
+    /// object or class, and parameters with the raw JNI types that match the signatures.
+    pub const unsafe fn new(
+        class_name: &'static str,
+        name: &'static str,
+        parameter_signatures: &'static [&'static str],
+        return_signature: &'static str,
+        function: *mut c_void,
+    ) -> Self {
+        NativeMethod {
+            class_name,
+            name,
+            parameter_signatures,
+            return_signature,
+            function,
+        }
+    }
+
+    /// Returns the name of the Java class that declares the method, in the JNI format.
+    pub fn class_name(&self) -> &'static str {
+        self.class_name
+    }
+
+    /// Returns the name of the Java method.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the JNI signature of the method.
+    pub fn signature(&self) -> String {
+        format!(
+            "({}){}",
+            self.parameter_signatures.concat(),
+            self.return_signature
+        )
+    }
+
+    /// Binds the Rust function to the Java method.
+    pub fn register(&self, env: &JnixEnv) -> Result<(), JnixError> {
+        let signature = self.signature();
+        let class = env
+            .try_get_class(self.class_name)
+            .map_err(|error| error.with_member(self.name, signature.clone()))?;
+        let method = jni::NativeMethod {
+            name: self.name.into(),
+            sig: signature.as_str().into(),
+            fn_ptr: self.function,
+        };
+
+        env.register_native_methods(&class, &[method]).for_member(
+            env,
+            self.class_name,
+            self.name,
+            signature,
+        )
+    }
+}
+
+/// The native methods that failed to be bound by [`JnixEnv::register_natives`].
+#[derive(Debug)]
+pub struct RegisterNativesError {
+    failures: Vec<JnixError>,
+}
+
+impl RegisterNativesError {
+    pub(crate) fn new(failures: Vec<JnixError>) -> Self {
+        RegisterNativesError { failures }
+    }
+
+    /// Returns the errors of the methods that failed to be bound.
+    ///
+    /// Each error contains the class, the method name and the signature of the failed method.
+    pub fn failures(&self) -> &[JnixError] {
+        &self.failures
+    }
+}
+
+impl Display for RegisterNativesError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "Failed to register {} native method(s)",
+            self.failures.len()
+        )?;
+
+        for failure in &self.failures {
+            write!(formatter, "\n  - {}", failure)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for RegisterNativesError {}
+
+/// Returns all native methods in the global registry.
+pub(crate) fn registered_native_methods() -> impl Iterator<Item = &'static NativeMethod> {
+    inventory::iter::<NativeMethod>.into_iter()
+}
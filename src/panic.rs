@@ -0,0 +1,98 @@
+use crate::{
+    into_java_exception::{cause_object, new_throwable},
+    IntoJavaException, JavaException, JnixEnv, JnixError,
+};
+use jni::objects::JThrowable;
+use std::{any::Any, borrow::Cow, cell::RefCell, panic, sync::Once};
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    /// The location of the last panic in the current thread, recorded by the panic hook.
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Installs a panic hook that records the location of panics, so that it can be included in the
+/// thrown exception.
+///
+/// The previously installed hook is still called afterwards.
+pub(crate) fn install_panic_location_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(ToString::to_string);
+            let _ = PANIC_LOCATION.try_with(|slot| *slot.borrow_mut() = location);
+
+            previous_hook(info);
+        }));
+    });
+}
+
+/// Forgets about any previously recorded panic location in the current thread.
+pub(crate) fn clear_panic_location() {
+    let _ = PANIC_LOCATION.try_with(|slot| slot.borrow_mut().take());
+}
+
+/// A caught panic that is converted into a Java exception.
+pub(crate) struct PanicException<'a> {
+    class_name: Cow<'a, str>,
+    message: String,
+    cause: Option<JavaException>,
+}
+
+impl<'a> PanicException<'a> {
+    /// Creates the exception from a panic payload.
+    ///
+    /// The panic location is included in the message if it was recorded by the panic hook. A Java
+    /// exception that was pending when the panic happened can be used as the cause.
+    pub fn new(
+        class_name: impl Into<Cow<'a, str>>,
+        payload: Box<dyn Any + Send>,
+        cause: Option<JavaException>,
+    ) -> Self {
+        let panic_message = if let Some(message) = payload.downcast_ref::<&str>() {
+            *message
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.as_str()
+        } else {
+            "Box<dyn Any>"
+        };
+
+        let location = PANIC_LOCATION
+            .try_with(|slot| slot.borrow_mut().take())
+            .ok()
+            .flatten();
+
+        let message = match location {
+            Some(location) => format!("Rust code panicked at {}: {}", location, panic_message),
+            None => format!("Rust code panicked: {}", panic_message),
+        };
+
+        PanicException {
+            class_name: class_name.into(),
+            message,
+            cause,
+        }
+    }
+}
+
+impl IntoJavaException for PanicException<'_> {
+    fn exception_class_name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.class_name)
+    }
+
+    fn exception_message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn to_java_exception<'env>(
+        &self,
+        env: &JnixEnv<'env>,
+        cause: Option<&JavaException>,
+    ) -> Result<JThrowable<'env>, JnixError> {
+        let cause = self.cause.as_ref().or(cause);
+
+        new_throwable(env, &self.class_name, &self.message, cause_object(cause))
+    }
+}
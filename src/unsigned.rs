@@ -0,0 +1,26 @@
+//! Wrappers that select how unsigned integers are converted.
+//!
+//! Java has no unsigned integer types, so by default `u8`, `u32` and `u64` are converted to the
+//! Java type of the same size (`byte`, `int` and `long`) by reinterpreting their bits, and `u16` is
+//! converted to `char`. Values larger than the maximum of the signed Java type become negative.
+//!
+//! The wrappers in this module select a different policy for a single value. Derived conversions
+//! can also select the policy for a field with the `#[jnix(unsigned = "...")]` attribute, using
+//! `"reinterpret"`, `"widen"` or `"checked"`.
+
+/// An unsigned integer converted to the next larger Java type, so that its value is preserved.
+///
+/// `u8` is converted to `short`, `u16` to `int`, `u32` to `long` and `u64` to
+/// `java.math.BigInteger`. Converting from Java fails if the value doesn't fit in the unsigned
+/// type.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Widened<T>(pub T);
+
+/// An unsigned integer converted to the Java type of the same size, failing if the value doesn't
+/// fit in it.
+///
+/// `u8` is converted to `byte`, `u16` to `char`, `u32` to `int` and `u64` to `long`. Converting
+/// into Java fails if the value is larger than the maximum of the signed Java type, and converting
+/// from Java fails if the value is negative.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Checked<T>(pub T);
@@ -0,0 +1,24 @@
+//! Conversion of strings through UTF-16.
+//!
+//! Java strings are sequences of UTF-16 code units, which aren't required to be valid UTF-16. A
+//! code unit in the surrogate range that isn't part of a surrogate pair can't be represented in a
+//! Rust string, so it is either replaced or reported as an error.
+
+/// How lone surrogates in Java strings are handled when they are converted into Rust strings.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LoneSurrogates {
+    /// Replace each lone surrogate with the U+FFFD replacement character.
+    Replace,
+
+    /// Fail the conversion if the Java string contains a lone surrogate.
+    Fail,
+}
+
+/// A string converted from Java that fails to convert if the Java string contains a lone
+/// surrogate.
+///
+/// A `String` replaces lone surrogates with the U+FFFD replacement character instead. Derived
+/// conversions can also select this behavior for a field with the
+/// `#[jnix(lone_surrogates = "fail")]` attribute.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct StrictString(pub String);
@@ -0,0 +1,175 @@
+//! A Java VM shared by the tests.
+//!
+//! The JVM library is loaded at run time from the Java installation in `JAVA_HOME`, or from the
+//! one that runs the `java` command, so that the tests don't need to link to it. The Java classes
+//! in `tests/java` are compiled with the `javac` command of the same installation, and added to
+//! the class path of the Java VM.
+
+use jnix::{
+    jni::{sys, JavaVM},
+    JnixEnv,
+};
+use libloading::Library;
+use once_cell::sync::Lazy;
+use std::{
+    env,
+    ffi::{c_void, CString},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    ptr,
+};
+
+type CreateJavaVm =
+    unsafe extern "system" fn(*mut *mut sys::JavaVM, *mut *mut c_void, *mut c_void) -> sys::jint;
+
+/// Paths of the JVM library relative to the Java home directory, in the layouts used by different
+/// platforms and Java versions.
+const JVM_LIBRARY_PATHS: &[&str] = &[
+    "lib/server/libjvm.so",
+    "lib/server/libjvm.dylib",
+    "bin/server/jvm.dll",
+    "jre/lib/amd64/server/libjvm.so",
+];
+
+/// The directory with the sources of the Java classes used by the tests.
+const JAVA_SOURCE_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/java");
+
+/// The directory where the Java classes are compiled, which is separate for each test crate so
+/// that test crates that run concurrently don't overwrite each other's classes.
+const JAVA_CLASS_DIRECTORY: &str = concat!(
+    env!("CARGO_TARGET_TMPDIR"),
+    "/java-classes/",
+    env!("CARGO_CRATE_NAME")
+);
+
+static JAVA_VM: Lazy<JavaVM> = Lazy::new(create_java_vm);
+
+/// Runs a test with a [`JnixEnv`] for the current thread, attached to the shared Java VM.
+pub fn with_env(test: impl FnOnce(&JnixEnv)) {
+    let attach_guard = JAVA_VM
+        .attach_current_thread()
+        .expect("Failed to attach test thread to the Java VM");
+
+    test(&JnixEnv::from(*attach_guard));
+}
+
+fn create_java_vm() -> JavaVM {
+    let java_home = find_java_home();
+    let library_path = find_jvm_library(&java_home);
+
+    compile_java_classes(&java_home);
+
+    // The library is never unloaded, because a Java VM can't be created again in the same process.
+    let library: &'static Library = Box::leak(Box::new(
+        unsafe { Library::new(&library_path) }
+            .unwrap_or_else(|error| panic!("Failed to load {}: {}", library_path.display(), error)),
+    ));
+    let create_java_vm = unsafe { library.get::<CreateJavaVm>(b"JNI_CreateJavaVM\0") }
+        .expect("Failed to find JNI_CreateJavaVM in the JVM library");
+
+    let check_jni = CString::new("-Xcheck:jni").unwrap();
+    let class_path = CString::new(format!("-Djava.class.path={}", JAVA_CLASS_DIRECTORY)).unwrap();
+    let mut options = [
+        sys::JavaVMOption {
+            optionString: check_jni.as_ptr() as *mut _,
+            extraInfo: ptr::null_mut(),
+        },
+        sys::JavaVMOption {
+            optionString: class_path.as_ptr() as *mut _,
+            extraInfo: ptr::null_mut(),
+        },
+    ];
+    let mut arguments = sys::JavaVMInitArgs {
+        version: sys::JNI_VERSION_1_8,
+        nOptions: options.len() as sys::jint,
+        options: options.as_mut_ptr(),
+        ignoreUnrecognized: sys::JNI_FALSE,
+    };
+    let mut java_vm = ptr::null_mut();
+    let mut env = ptr::null_mut();
+
+    let result = unsafe {
+        create_java_vm(
+            &mut java_vm,
+            &mut env,
+            &mut arguments as *mut sys::JavaVMInitArgs as *mut c_void,
+        )
+    };
+
+    assert_eq!(result, sys::JNI_OK, "Failed to create the Java VM");
+
+    unsafe { JavaVM::from_raw(java_vm) }.expect("Invalid Java VM pointer")
+}
+
+fn find_java_home() -> PathBuf {
+    env::var_os("JAVA_HOME")
+        .map(PathBuf::from)
+        .or_else(java_home_of_java_command)
+        .expect("Failed to find a Java installation, try setting JAVA_HOME")
+}
+
+fn find_jvm_library(java_home: &Path) -> PathBuf {
+    JVM_LIBRARY_PATHS
+        .iter()
+        .map(|relative_path| java_home.join(relative_path))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| panic!("Failed to find the JVM library in {}", java_home.display()))
+}
+
+/// Asks the `java` command for its home directory.
+fn java_home_of_java_command() -> Option<PathBuf> {
+    let output = Command::new("java")
+        .args(["-XshowSettings:properties", "-version"])
+        .output()
+        .ok()?;
+    let settings = String::from_utf8_lossy(&output.stderr);
+
+    settings.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+
+        if key.trim() == "java.home" {
+            Some(Path::new(value.trim()).to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+/// Compiles the Java sources into [`JAVA_CLASS_DIRECTORY`].
+fn compile_java_classes(java_home: &Path) {
+    let mut sources = Vec::new();
+
+    find_java_sources(Path::new(JAVA_SOURCE_DIRECTORY), &mut sources);
+
+    // A JRE may be inside the JDK directory, in older Java versions
+    let javac = [java_home, java_home.parent().unwrap_or(java_home)]
+        .iter()
+        .map(|home| home.join("bin").join("javac"))
+        .find(|path| path.is_file() || path.with_extension("exe").is_file())
+        .unwrap_or_else(|| PathBuf::from("javac"));
+
+    let status = Command::new(&javac)
+        .arg("-d")
+        .arg(JAVA_CLASS_DIRECTORY)
+        .args(&sources)
+        .status()
+        .unwrap_or_else(|error| panic!("Failed to run {}: {}", javac.display(), error));
+
+    assert!(status.success(), "Failed to compile the Java test classes");
+}
+
+fn find_java_sources(directory: &Path, sources: &mut Vec<PathBuf>) {
+    let entries = fs::read_dir(directory)
+        .unwrap_or_else(|error| panic!("Failed to read {}: {}", directory.display(), error));
+
+    for entry in entries {
+        let path = entry.expect("Failed to read directory entry").path();
+
+        if path.is_dir() {
+            find_java_sources(&path, sources);
+        } else if path.extension() == Some("java".as_ref()) {
+            sources.push(path);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::with_env;
+use jnix::{
+    jni::objects::{JObject, JValue},
+    IntoJavaException, JavaException, JnixEnv,
+};
+use std::borrow::Cow;
+
+/// An error that is thrown as a `java.lang.UnsupportedOperationException`.
+struct UnsupportedError;
+
+impl IntoJavaException for UnsupportedError {
+    fn exception_class_name(&self) -> Cow<'_, str> {
+        Cow::Borrowed("java/lang/UnsupportedOperationException")
+    }
+
+    fn exception_message(&self) -> String {
+        "unsupported thing".to_owned()
+    }
+}
+
+/// Makes a JNI call that fails, leaving a `java.lang.NumberFormatException` pending.
+fn fail_to_parse_integer(env: &JnixEnv) {
+    let text = env.new_string("not a number").unwrap();
+    let result = env.call_static_method(
+        "java/lang/Integer",
+        "parseInt",
+        "(Ljava/lang/String;)I",
+        &[JValue::Object(JObject::from(text))],
+    );
+
+    assert!(matches!(
+        result,
+        Err(jnix::jni::errors::Error::JavaException)
+    ));
+    assert!(env.exception_check().unwrap());
+}
+
+/// Takes the thrown exception from the environment, checking that there was one.
+fn take_thrown_exception(env: &JnixEnv) -> JavaException {
+    env.take_exception().expect("No exception was thrown")
+}
+
+#[test]
+fn throw_exception() {
+    with_env(|env| {
+        env.throw_exception(&UnsupportedError);
+
+        let exception = take_thrown_exception(env);
+
+        assert_eq!(
+            exception.class_name(),
+            "java.lang.UnsupportedOperationException"
+        );
+        assert_eq!(exception.message(), Some("unsupported thing"));
+        assert!(exception.cause().is_none());
+    });
+}
+
+#[test]
+fn throw_exception_with_pending_exception() {
+    with_env(|env| {
+        fail_to_parse_integer(env);
+
+        env.throw_exception(&UnsupportedError);
+
+        let exception = take_thrown_exception(env);
+
+        assert_eq!(
+            exception.class_name(),
+            "java.lang.UnsupportedOperationException"
+        );
+        assert_eq!(exception.message(), Some("unsupported thing"));
+        assert_eq!(
+            exception.cause().map(JavaException::class_name),
+            Some("java.lang.NumberFormatException")
+        );
+    });
+}
+
+#[test]
+fn throw_error_result_with_pending_exception() {
+    with_env(|env| {
+        fail_to_parse_integer(env);
+
+        assert_eq!(env.unwrap_or_throw(Err::<(), _>(UnsupportedError)), None);
+
+        let exception = take_thrown_exception(env);
+
+        assert_eq!(
+            exception.class_name(),
+            "java.lang.UnsupportedOperationException"
+        );
+        assert_eq!(
+            exception.cause().map(JavaException::class_name),
+            Some("java.lang.NumberFormatException")
+        );
+    });
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use common::with_env;
+use jnix::{
+    jni::{
+        objects::{JObject, JValue},
+        JNIEnv,
+    },
+    FromJava, IntoJava, JavaException, JnixEnv,
+};
+use jnix_macros::{export, IntoJavaException};
+use std::fmt::{self, Display, Formatter};
+
+const CLASS: &str = "jnix/test/Exported";
+
+#[derive(Debug, IntoJavaException)]
+#[jnix(exception_class = "java.lang.IllegalArgumentException")]
+struct InvalidNumber(String);
+
+impl Display for InvalidNumber {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "Invalid number: {}", self.0)
+    }
+}
+
+#[export(class = "jnix.test.Exported")]
+fn add(first: i32, second: i32) -> i32 {
+    first + second
+}
+
+#[export(class = "jnix.test.Exported")]
+fn describe(_env: &JnixEnv, flag: bool, text: Option<String>) -> String {
+    format!("{} {:?}", flag, text)
+}
+
+#[export(class = "jnix.test.Exported")]
+fn sum(values: Vec<i32>) -> i64 {
+    values.into_iter().map(i64::from).sum()
+}
+
+#[export(class = "jnix.test.Exported")]
+fn parse_number(text: String) -> Result<i32, InvalidNumber> {
+    text.parse().map_err(|_| InvalidNumber(text))
+}
+
+#[export(class = "jnix.test.Exported")]
+fn check_positive(value: i32) -> Result<(), InvalidNumber> {
+    if value > 0 {
+        Ok(())
+    } else {
+        Err(InvalidNumber(value.to_string()))
+    }
+}
+
+#[export(class = "jnix.test.Exported")]
+fn fail(message: String) -> String {
+    panic!("{}", message)
+}
+
+#[export(class = "jnix.test.Exported", name = "twice", signature = "(I)I")]
+fn twice_number(value: i32) -> i32 {
+    value * 2
+}
+
+#[export(
+    class = "jnix.test.Exported",
+    name = "twice",
+    signature = "(Ljava/lang/String;)Ljava/lang/String;"
+)]
+fn twice_text(value: String) -> String {
+    value.repeat(2)
+}
+
+/// Runs a test after binding the exported native methods to the Java class.
+fn with_registered_env(test: impl FnOnce(&JnixEnv)) {
+    with_env(|env| {
+        if let Err(error) = env.register_natives() {
+            panic!("{}", error);
+        }
+
+        test(env)
+    });
+}
+
+fn call<'env>(
+    env: &JnixEnv<'env>,
+    name: &str,
+    signature: &str,
+    arguments: &[JValue<'env>],
+) -> JValue<'env> {
+    env.call_static_method(CLASS, name, signature, arguments)
+        .unwrap_or_else(|error| panic!("Failed to call {}: {}", name, error))
+}
+
+/// Calls a native method that is expected to throw, and returns the thrown exception.
+fn call_and_catch(
+    env: &JnixEnv,
+    name: &str,
+    signature: &str,
+    arguments: &[JValue],
+) -> JavaException {
+    assert!(env
+        .call_static_method(CLASS, name, signature, arguments)
+        .is_err());
+
+    env.take_exception().expect("No exception was thrown")
+}
+
+/// Returns the `JNIEnv` to pass to the generated functions when calling them directly.
+fn raw_env<'env>(env: &JnixEnv<'env>) -> JNIEnv<'env> {
+    unsafe { JNIEnv::from_raw(env.get_native_interface()) }.unwrap()
+}
+
+#[test]
+fn primitive_arguments_and_return_value() {
+    with_registered_env(|env| {
+        let result = call(env, "add", "(II)I", &[JValue::Int(2), JValue::Int(3)]);
+
+        assert_eq!(result.i().unwrap(), 5);
+    });
+}
+
+#[test]
+fn object_arguments_and_return_value() {
+    with_registered_env(|env| {
+        let text = "text".to_owned().into_java(env);
+        let result = call(
+            env,
+            "describe",
+            "(ZLjava/lang/String;)Ljava/lang/String;",
+            &[JValue::Bool(1), JValue::Object(text.as_obj())],
+        );
+
+        assert_eq!(
+            String::from_java(env, result.l().unwrap()),
+            "true Some(\"text\")"
+        );
+
+        let result = call(
+            env,
+            "describe",
+            "(ZLjava/lang/String;)Ljava/lang/String;",
+            &[JValue::Bool(0), JValue::Object(JObject::null())],
+        );
+
+        assert_eq!(String::from_java(env, result.l().unwrap()), "false None");
+
+        let values = vec![1, 2, i32::MAX].into_java(env);
+        let result = call(env, "sum", "([I)J", &[JValue::Object(values.as_obj())]);
+
+        assert_eq!(result.j().unwrap(), i64::from(i32::MAX) + 3);
+    });
+}
+
+#[test]
+fn overloaded_methods() {
+    with_registered_env(|env| {
+        let result = call(env, "twice", "(I)I", &[JValue::Int(21)]);
+
+        assert_eq!(result.i().unwrap(), 42);
+
+        let text = "ab".to_owned().into_java(env);
+        let result = call(
+            env,
+            "twice",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[JValue::Object(text.as_obj())],
+        );
+
+        assert_eq!(String::from_java(env, result.l().unwrap()), "abab");
+    });
+}
+
+#[test]
+fn errors_are_thrown() {
+    with_registered_env(|env| {
+        let text = "12".to_owned().into_java(env);
+        let result = call(
+            env,
+            "parseNumber",
+            "(Ljava/lang/String;)I",
+            &[JValue::Object(text.as_obj())],
+        );
+
+        assert_eq!(result.i().unwrap(), 12);
+
+        let text = "twelve".to_owned().into_java(env);
+        let exception = call_and_catch(
+            env,
+            "parseNumber",
+            "(Ljava/lang/String;)I",
+            &[JValue::Object(text.as_obj())],
+        );
+
+        assert_eq!(exception.class_name(), "java.lang.IllegalArgumentException");
+        assert_eq!(exception.message(), Some("Invalid number: twelve"));
+
+        call(env, "checkPositive", "(I)V", &[JValue::Int(1)]);
+
+        let exception = call_and_catch(env, "checkPositive", "(I)V", &[JValue::Int(-1)]);
+
+        assert_eq!(exception.class_name(), "java.lang.IllegalArgumentException");
+        assert_eq!(exception.message(), Some("Invalid number: -1"));
+    });
+}
+
+#[test]
+fn panics_are_thrown_with_their_location() {
+    with_registered_env(|env| {
+        let message = "kaboom".to_owned().into_java(env);
+        let exception = call_and_catch(
+            env,
+            "fail",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[JValue::Object(message.as_obj())],
+        );
+        let exception_message = exception.message().unwrap();
+
+        assert_eq!(exception.class_name(), "java.lang.RuntimeException");
+        assert!(
+            exception_message.starts_with("Rust code panicked at tests/export.rs:"),
+            "{}",
+            exception_message
+        );
+        assert!(
+            exception_message.ends_with(": kaboom"),
+            "{}",
+            exception_message
+        );
+    });
+}
+
+#[test]
+fn default_values_are_returned_with_exceptions() {
+    with_registered_env(|env| {
+        let text = "twelve".to_owned().into_java(env);
+        let result =
+            Java_jnix_test_Exported_parseNumber(raw_env(env), JObject::null(), text.as_obj());
+
+        assert_eq!(result, 0);
+        assert!(env.take_exception().is_some());
+
+        let message = "kaboom".to_owned().into_java(env);
+        let result = Java_jnix_test_Exported_fail(raw_env(env), JObject::null(), message.as_obj());
+
+        assert!(result.is_null());
+        assert!(env.take_exception().is_some());
+    });
+}
@@ -0,0 +1,20 @@
+package jnix.test;
+
+/** Native methods implemented by the functions exported in `tests/export.rs`. */
+public class Exported {
+    public static native int add(int first, int second);
+
+    public static native String describe(boolean flag, String text);
+
+    public static native long sum(int[] values);
+
+    public static native int parseNumber(String text);
+
+    public static native void checkPositive(int value);
+
+    public static native String fail(String message);
+
+    public static native int twice(int value);
+
+    public static native String twice(String value);
+}
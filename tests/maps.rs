@@ -0,0 +1,77 @@
+mod common;
+
+use common::with_env;
+use jnix::{jni::objects::JObject, FromJava, IntoJava, JnixEnv, TryFromJava, TryIntoJava};
+use std::collections::{BTreeMap, HashMap};
+
+/// Checks that the map type has the same signature in both directions, and that the map is
+/// converted into a Java map of the expected class and back.
+macro_rules! assert_round_trip {
+    ($env:expr, $map_type:ty, $map:expr, $class_name:expr) => {{
+        let env: &JnixEnv = $env;
+        let map: $map_type = $map;
+
+        assert_eq!(<$map_type as IntoJava>::JNI_SIGNATURE, "Ljava/util/Map;");
+        assert_eq!(<$map_type as TryIntoJava>::JNI_SIGNATURE, "Ljava/util/Map;");
+        assert_eq!(
+            <$map_type as FromJava<JObject>>::JNI_SIGNATURE,
+            "Ljava/util/Map;"
+        );
+        assert_eq!(
+            <$map_type as TryFromJava<JObject>>::JNI_SIGNATURE,
+            "Ljava/util/Map;"
+        );
+
+        let java_map = map.clone().into_java(env);
+        let class = env.find_class($class_name).unwrap();
+
+        assert!(env.is_instance_of(java_map.as_obj(), class).unwrap());
+        assert_eq!(<$map_type>::from_java(env, java_map.as_obj()), map);
+
+        let java_map = map.clone().try_into_java(env).unwrap();
+
+        assert_eq!(
+            <$map_type>::try_from_java(env, java_map.as_obj()).unwrap(),
+            map
+        );
+    }};
+}
+
+#[test]
+fn hash_map() {
+    with_env(|env| {
+        assert_round_trip!(
+            env,
+            HashMap<i32, String>,
+            HashMap::from([(1, "one".to_owned()), (2, "two".to_owned())]),
+            "java/util/HashMap"
+        )
+    });
+}
+
+#[test]
+fn btree_map() {
+    with_env(|env| {
+        assert_round_trip!(
+            env,
+            BTreeMap<String, i32>,
+            BTreeMap::from([("a".to_owned(), 1), ("b".to_owned(), 2)]),
+            "java/util/LinkedHashMap"
+        )
+    });
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn index_map() {
+    use indexmap::IndexMap;
+
+    with_env(|env| {
+        assert_round_trip!(
+            env,
+            IndexMap<String, i64>,
+            IndexMap::from([("b".to_owned(), 2), ("a".to_owned(), 1)]),
+            "java/util/LinkedHashMap"
+        )
+    });
+}
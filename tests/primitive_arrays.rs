@@ -0,0 +1,195 @@
+mod common;
+
+use common::with_env;
+use jnix::{jni::objects::JObject, FromJava, IntoJava, JnixEnv, TryFromJava, TryIntoJava};
+
+/// Converts the values into a Java array and back, checking that the array has the expected
+/// signature and that `Vec`, `Box<[T]>` and slices are converted in the same way.
+macro_rules! assert_round_trip {
+    ($env:expr, $element_type:ty, $signature:expr, $values:expr) => {{
+        let env: &JnixEnv = $env;
+        let values: Vec<$element_type> = $values.to_vec();
+
+        assert_eq!(<Vec<$element_type> as IntoJava>::JNI_SIGNATURE, $signature);
+
+        let array = values.clone().into_java(env);
+        assert_is_instance_of(env, array.as_obj(), $signature);
+        assert_eq!(Vec::<$element_type>::from_java(env, array.as_obj()), values);
+
+        let array = values.clone().into_boxed_slice().into_java(env);
+        assert_eq!(
+            Box::<[$element_type]>::from_java(env, array.as_obj()),
+            values.clone().into_boxed_slice()
+        );
+
+        let array = values.as_slice().into_java(env);
+        assert_eq!(Vec::<$element_type>::from_java(env, array.as_obj()), values);
+    }};
+}
+
+fn assert_is_instance_of(env: &JnixEnv, object: JObject, signature: &str) {
+    let class = env
+        .find_class(signature)
+        .expect("Failed to find array class");
+
+    assert!(env.is_instance_of(object, class).unwrap());
+}
+
+#[test]
+fn booleans() {
+    with_env(|env| assert_round_trip!(env, bool, "[Z", [true, false, true]));
+}
+
+#[test]
+fn signed_integers() {
+    with_env(|env| {
+        assert_round_trip!(env, i8, "[B", [i8::MIN, -1, 0, 1, i8::MAX]);
+        assert_round_trip!(env, i16, "[S", [i16::MIN, -1, 0, 1, i16::MAX]);
+        assert_round_trip!(env, i32, "[I", [i32::MIN, -1, 0, 1, i32::MAX]);
+        assert_round_trip!(env, i64, "[J", [i64::MIN, -1, 0, 1, i64::MAX]);
+    });
+}
+
+#[test]
+fn floating_point_numbers() {
+    with_env(|env| {
+        assert_round_trip!(env, f32, "[F", [f32::MIN, -1.5, 0.0, 1.5, f32::MAX]);
+        assert_round_trip!(env, f64, "[D", [f64::MIN, -1.5, 0.0, 1.5, f64::MAX]);
+    });
+}
+
+#[test]
+fn unsigned_integers() {
+    with_env(|env| {
+        assert_round_trip!(env, u8, "[B", [0, 1, 127, 128, u8::MAX]);
+        assert_round_trip!(env, u16, "[C", [0, 1, 0x7fff, 0xd800, u16::MAX]);
+        assert_round_trip!(env, u32, "[I", [0, 1, 0x7fff_ffff, 0x8000_0000, u32::MAX]);
+        assert_round_trip!(env, u64, "[J", [0, 1, 1u64 << 63, u64::MAX]);
+    });
+}
+
+#[test]
+fn unsigned_integers_are_reinterpreted_as_signed_java_integers() {
+    with_env(|env| {
+        let bytes = vec![0u8, 127, 128, 255].into_java(env);
+        assert_eq!(
+            Vec::<i8>::from_java(env, bytes.as_obj()),
+            [0, 127, -128, -1]
+        );
+
+        let ints = vec![0u32, 0x8000_0000, u32::MAX].into_java(env);
+        assert_eq!(Vec::<i32>::from_java(env, ints.as_obj()), [0, i32::MIN, -1]);
+
+        let longs = vec![0u64, 1 << 63, u64::MAX].into_java(env);
+        assert_eq!(
+            Vec::<i64>::from_java(env, longs.as_obj()),
+            [0, i64::MIN, -1]
+        );
+
+        let chars = vec![0u16, 0xd800, u16::MAX].into_java(env);
+        assert_eq!(
+            Vec::<u16>::from_java(env, chars.as_obj()),
+            [0, 0xd800, u16::MAX]
+        );
+    });
+}
+
+#[test]
+fn empty_arrays() {
+    with_env(|env| {
+        assert_round_trip!(env, bool, "[Z", [] as [bool; 0]);
+        assert_round_trip!(env, u8, "[B", [] as [u8; 0]);
+        assert_round_trip!(env, u64, "[J", [] as [u64; 0]);
+        assert_round_trip!(env, char, "[C", [] as [char; 0]);
+    });
+}
+
+#[test]
+fn characters_in_the_basic_multilingual_plane() {
+    with_env(|env| assert_round_trip!(env, char, "[C", ['a', '\0', 'ü', '日', '\u{ffff}']));
+}
+
+#[test]
+fn characters_outside_the_basic_multilingual_plane_fail_to_convert() {
+    with_env(|env| {
+        assert!(vec!['a', '😀'].try_into_java(env).is_err());
+        assert!(['😀'].try_into_java(env).is_err());
+    });
+}
+
+#[test]
+fn surrogates_fail_to_convert_into_characters() {
+    with_env(|env| {
+        let high_surrogate = vec![0x61u16, 0xd83d].into_java(env);
+        let low_surrogate = vec![0xde00u16].into_java(env);
+        let surrogate_pair = vec![0xd83du16, 0xde00].into_java(env);
+
+        assert!(Vec::<char>::try_from_java(env, high_surrogate.as_obj()).is_err());
+        assert!(Vec::<char>::try_from_java(env, low_surrogate.as_obj()).is_err());
+        assert!(<[char; 2]>::try_from_java(env, surrogate_pair.as_obj()).is_err());
+    });
+}
+
+#[test]
+fn fixed_size_arrays_check_the_length() {
+    with_env(|env| {
+        let array = [1i32, 2, 3].into_java(env);
+
+        assert_eq!(
+            <[i32; 3]>::try_from_java(env, array.as_obj()).unwrap(),
+            [1, 2, 3]
+        );
+        assert_eq!(Vec::<i32>::from_java(env, array.as_obj()), [1, 2, 3]);
+        assert!(<[i32; 2]>::try_from_java(env, array.as_obj()).is_err());
+        assert!(<[i32; 4]>::try_from_java(env, array.as_obj()).is_err());
+    });
+}
+
+#[test]
+fn fixed_size_arrays_of_all_element_types() {
+    with_env(|env| {
+        let array = [true, false].into_java(env);
+        assert_eq!(<[bool; 2]>::from_java(env, array.as_obj()), [true, false]);
+
+        let array = [i8::MIN, 1].into_java(env);
+        assert_eq!(<[i8; 2]>::from_java(env, array.as_obj()), [i8::MIN, 1]);
+
+        let array = [i16::MIN, 1].into_java(env);
+        assert_eq!(<[i16; 2]>::from_java(env, array.as_obj()), [i16::MIN, 1]);
+
+        let array = [i64::MIN, 1].into_java(env);
+        assert_eq!(<[i64; 2]>::from_java(env, array.as_obj()), [i64::MIN, 1]);
+
+        let array = [u8::MAX, 1].into_java(env);
+        assert_eq!(<[u8; 2]>::from_java(env, array.as_obj()), [u8::MAX, 1]);
+
+        let array = [u16::MAX, 1].into_java(env);
+        assert_eq!(<[u16; 2]>::from_java(env, array.as_obj()), [u16::MAX, 1]);
+
+        let array = [u32::MAX, 1].into_java(env);
+        assert_eq!(<[u32; 2]>::from_java(env, array.as_obj()), [u32::MAX, 1]);
+
+        let array = [u64::MAX, 1].into_java(env);
+        assert_eq!(<[u64; 2]>::from_java(env, array.as_obj()), [u64::MAX, 1]);
+
+        let array = [-1.5f32, 2.5].into_java(env);
+        assert_eq!(<[f32; 2]>::from_java(env, array.as_obj()), [-1.5, 2.5]);
+
+        let array = [-1.5f64, 2.5].into_java(env);
+        assert_eq!(<[f64; 2]>::from_java(env, array.as_obj()), [-1.5, 2.5]);
+
+        let array = ['ü', 'x'].into_java(env);
+        assert_eq!(<[char; 2]>::from_java(env, array.as_obj()), ['ü', 'x']);
+    });
+}
+
+#[test]
+fn arrays_with_a_different_element_type_fail_to_convert() {
+    with_env(|env| {
+        let array = vec![1i32, 2].into_java(env);
+
+        assert!(Vec::<i64>::try_from_java(env, array.as_obj()).is_err());
+        assert!(Vec::<bool>::try_from_java(env, array.as_obj()).is_err());
+        assert!(Vec::<i64>::try_from_java(env, JObject::null()).is_err());
+    });
+}
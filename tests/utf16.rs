@@ -0,0 +1,152 @@
+mod common;
+
+use common::with_env;
+use jnix::{
+    jni::objects::{JObject, JString, JValue},
+    FromJava, IntoJava, JnixEnv, LoneSurrogates,
+};
+
+/// Creates a Java string with the UTF-16 code units, which may contain lone surrogates.
+fn java_string_from_code_units<'env>(env: &JnixEnv<'env>, code_units: &[u16]) -> JString<'env> {
+    let characters = code_units.into_java(env);
+    let string = env
+        .new_object(
+            "java/lang/String",
+            "([C)V",
+            &[JValue::Object(characters.as_obj())],
+        )
+        .expect("Failed to create Java string");
+
+    JString::from(string)
+}
+
+/// Reads the UTF-16 code units of a Java string.
+fn code_units_of(env: &JnixEnv, string: JString) -> Vec<u16> {
+    let characters = env
+        .call_method(string, "toCharArray", "()[C", &[])
+        .and_then(|characters| characters.l())
+        .expect("Failed to get characters of Java string");
+
+    Vec::<u16>::from_java(env, characters)
+}
+
+fn assert_round_trip(env: &JnixEnv, string: &str) {
+    let java_string = env.new_utf16_string(string).unwrap();
+
+    assert_eq!(
+        code_units_of(env, java_string),
+        string.encode_utf16().collect::<Vec<_>>()
+    );
+
+    for lone_surrogates in [LoneSurrogates::Replace, LoneSurrogates::Fail] {
+        assert_eq!(
+            env.get_utf16_string(java_string, lone_surrogates).unwrap(),
+            string
+        );
+    }
+}
+
+#[test]
+fn empty_string() {
+    with_env(|env| assert_round_trip(env, ""));
+}
+
+#[test]
+fn ascii_string() {
+    with_env(|env| assert_round_trip(env, "Hello, world!"));
+}
+
+#[test]
+fn non_ascii_string() {
+    with_env(|env| assert_round_trip(env, "Größe, ĳ, 日本語"));
+}
+
+#[test]
+fn emoji_are_surrogate_pairs() {
+    with_env(|env| {
+        assert_round_trip(env, "😀");
+        assert_round_trip(env, "emoji 🦀 in the middle");
+
+        let java_string = env.new_utf16_string("😀").unwrap();
+
+        assert_eq!(code_units_of(env, java_string), [0xd83d, 0xde00]);
+    });
+}
+
+#[test]
+fn embedded_nul_characters() {
+    with_env(|env| {
+        assert_round_trip(env, "\0");
+        assert_round_trip(env, "a\0b");
+        assert_round_trip(env, "ü\0😀\0");
+    });
+}
+
+#[test]
+fn lone_high_surrogate() {
+    with_env(|env| {
+        let java_string = java_string_from_code_units(env, &[0x61, 0xd83d, 0x62]);
+
+        assert_eq!(
+            env.get_utf16_string(java_string, LoneSurrogates::Replace)
+                .unwrap(),
+            "a\u{fffd}b"
+        );
+        assert!(env
+            .get_utf16_string(java_string, LoneSurrogates::Fail)
+            .is_err());
+    });
+}
+
+#[test]
+fn lone_high_surrogate_at_the_end() {
+    with_env(|env| {
+        let java_string = java_string_from_code_units(env, &[0x61, 0xd83d]);
+
+        assert_eq!(
+            env.get_utf16_string(java_string, LoneSurrogates::Replace)
+                .unwrap(),
+            "a\u{fffd}"
+        );
+        assert!(env
+            .get_utf16_string(java_string, LoneSurrogates::Fail)
+            .is_err());
+    });
+}
+
+#[test]
+fn lone_low_surrogate() {
+    with_env(|env| {
+        let java_string = java_string_from_code_units(env, &[0xde00, 0x61]);
+
+        assert_eq!(
+            env.get_utf16_string(java_string, LoneSurrogates::Replace)
+                .unwrap(),
+            "\u{fffd}a"
+        );
+        assert!(env
+            .get_utf16_string(java_string, LoneSurrogates::Fail)
+            .is_err());
+    });
+}
+
+#[test]
+fn null_string_is_an_error() {
+    with_env(|env| {
+        let null_string = JString::from(JObject::null());
+
+        for lone_surrogates in [LoneSurrogates::Replace, LoneSurrogates::Fail] {
+            assert!(env.get_utf16_string(null_string, lone_surrogates).is_err());
+        }
+    });
+}
+
+#[test]
+fn string_conversions() {
+    with_env(|env| {
+        let string = "a\0😀".to_owned();
+        let java_string = string.clone().into_java(env);
+
+        assert_eq!(String::from_java(env, java_string.as_obj()), string);
+    });
+}